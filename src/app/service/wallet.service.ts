@@ -8,6 +8,46 @@ import {
 
 const nearConfig = getConfig('development') // TODO use process.env.NODE_ENV || 'development'
 const toNear = (balance: string) => Math.floor(parseFloat(formatNearAmount(balance)) * 100) / 100
+// Stable codes of the contract `SocietyError`, the panic message is `CODE: message`
+const ERROR_MESSAGES: { [code: string]: string } = {
+  ERR_ONLY_MEMBER: 'Only members can do this',
+  ERR_ALREADY_MEMBER: 'The account is already a member',
+  ERR_ALREADY_VOTED: 'You have already voted',
+  ERR_BOND_REQUIRED: 'The proposal bond is not attached',
+  ERR_NOT_REGISTERED: 'Deposit the storage before',
+  ERR_STORAGE_DEPOSIT: 'Deposit more for the storage',
+  ERR_REGISTRATION_DEPOSIT: 'The deposit does not cover the registration',
+  ERR_NOT_ENOUGH_FUND: 'The fund does not have so many resources',
+  ERR_PROPOSAL_NOT_FOUND: 'The proposal is not found',
+  ERR_PROPOSAL_FINALIZED: 'The proposal is already finalized',
+  ERR_PROPOSAL_NOT_IN_VOTE: 'The proposal is a draft, update it to vote again',
+  ERR_PROPOSAL_NOT_DRAFT: 'The proposal can change only in the draft status',
+  ERR_PROPOSAL_LIMIT: 'You have too many open proposals of this kind',
+  ERR_FIELD_LENGTH: 'The text is too long',
+  ERR_INVALID_SCRIPT: 'The proposal script is invalid',
+  ERR_ZERO_VALUE: 'The amount cannot be zero',
+  ERR_DUES_DISABLED: 'Dues are not enabled',
+  ERR_DUES_REQUIRED: 'The deposit does not cover the dues',
+  ERR_DUES_LAPSED: 'Pay the dues to vote',
+  ERR_DUES_OVERFLOW: 'The deposit covers too many dues periods',
+  ERR_DUES_PERIOD: 'The dues period and grace are too long',
+}
+
+export class SocietyError extends Error {
+  constructor(public code: string, message: string) {
+    super(message)
+  }
+}
+
+const toSocietyError = (reason: any): Error => {
+  const match = /(ERR_[A-Z_]+): ([^"\\\n]*)/.exec(reason?.message || '')
+  if (!match) {
+    return reason
+  }
+  const [, code, message] = match
+  return new SocietyError(code, ERROR_MESSAGES[code] || message)
+}
+
 interface ProposalVote {
   approve: number
   reject: number
@@ -17,6 +57,42 @@ interface FundScript {
   fund: string
 }
 
+interface FundBalance {
+  liquid: string
+  staked: string
+  unstaking: string
+  royalty: string
+}
+
+interface StorageBalance {
+  total: string
+  available: string
+}
+
+interface DuesPolicy {
+  amount: string
+  period: string
+  grace: string
+}
+
+interface Policy {
+  proposal_bond: string
+  dues: DuesPolicy | null
+}
+
+interface MemberStanding {
+  account_id: string
+  paid_through: string | null
+  lapsed: boolean
+}
+
+interface Attachment {
+  uri: string
+  hash: string
+  mime_type: string
+  label: string
+}
+
 interface Proposal {
   id: number
   title: string
@@ -26,6 +102,40 @@ interface Proposal {
   author: string
   script: string
   vote: ProposalVote
+  archived: boolean
+  attachments: Attachment[]
+}
+
+interface ProposalRevision {
+  timestamp: string
+  title: string
+  description: string
+  script: string | null
+  attachments: Attachment[]
+}
+
+interface Comment {
+  id: number
+  author: string
+  timestamp: string
+  text: string | null
+  reply_to: number | null
+  hidden: boolean
+}
+
+interface MemberProfile {
+  account_id: string
+  name: string
+  avatar: string | null
+  bio: string
+  links: string[]
+  joined_at: string
+  admitted_by_proposal: number | null
+}
+
+interface ProposalPage {
+  total: number | null
+  proposals: Proposal[]
 }
 
 interface ProposalOfAccount {
@@ -35,19 +145,49 @@ interface ProposalOfAccount {
 }
 
 interface Society extends Contract {
-  balance(): Promise<string>
+  balance(): Promise<FundBalance>
+
+  policy(): Promise<Policy>
+
+  storage_balance_of(param: Object): Promise<StorageBalance | null>
+
+  storage_deposit(param: Object, gas: string, amount: string): Promise<StorageBalance>
 
   member_list(): Promise<string[]>
 
+  member(param: Object): Promise<MemberProfile | null>
+
+  member_profiles(param: Object): Promise<MemberProfile[]>
+
+  member_standing(param: Object): Promise<MemberStanding | null>
+
+  member_standings(param: Object): Promise<MemberStanding[]>
+
+  pay_dues(param: Object, gas: string, amount: string): Promise<string>
+
+  update_profile(param: Object, gas: string, amount: string): Promise<MemberProfile>
+
   proposal_list(): Promise<Proposal[]>
 
+  proposals(param: Object): Promise<ProposalPage>
+
+  get_proposal(param: Object): Promise<Proposal | null>
+
+  active_proposal_of(param: Object): Promise<number[]>
+
+  proposals_by_author(param: Object): Promise<Proposal[]>
+
+  proposal_revisions(param: Object): Promise<ProposalRevision[]>
+
+  proposal_comments(param: Object): Promise<Comment[]>
+
   is_member(param: Object): Promise<boolean>
 
   can_vote(param: Object): Promise<boolean>
 
-  add_member_proposal(param: Object): Promise<number>
+  add_member_proposal(param: Object, gas: string, amount: string): Promise<number>
 
-  add_fund_proposal(param: Object): Promise<number>
+  add_fund_proposal(param: Object, gas: string, amount: string): Promise<number>
 
   vote_reject(param: Object, gas: string, amount: string): Promise<void>
 
@@ -63,7 +203,9 @@ export class WalletService {
   contract: Society
   accountId: string
   balance: number = 0
+  storageBalance: number = 0
   memberList: string[] = []
+  memberProfileList: MemberProfile[] = []
   proposalList: ProposalOfAccount[] = []
   proposalArchiveList: ProposalOfAccount[] = []
   proposalActiveList: ProposalOfAccount[] = []
@@ -94,14 +236,29 @@ export class WalletService {
     this.contract = new Contract(this.connection.account(), this.contractName, {
       viewMethods: [
         'balance',
+        'policy',
+        'storage_balance_of',
         'member_list',
+        'member',
+        'member_profiles',
+        'member_standing',
+        'member_standings',
         'proposal_list',
+        'proposals',
+        'get_proposal',
+        'active_proposal_of',
+        'proposals_by_author',
+        'proposal_revisions',
+        'proposal_comments',
         'is_member',
         'can_vote',
       ],
       changeMethods: [
         'add_fund_proposal',
         'add_member_proposal',
+        'storage_deposit',
+        'pay_dues',
+        'update_profile',
         'vote_approve',
         'vote_reject',
       ],
@@ -112,16 +269,65 @@ export class WalletService {
   async update(): Promise<void> {
     this.isMember = await this.updateStatusMember(this.accountId)
     await this.updateBalance()
+    await this.updateStorageBalance()
     await this.updateMemberList()
     await this.updateProposalList()
   }
 
   async updateBalance(): Promise<void> {
-    this.balance = toNear(await this.contract.balance())
+    this.balance = toNear((await this.contract.balance()).liquid)
+  }
+
+  async updateStorageBalance(): Promise<void> {
+    if (!this.accountId) {
+      return
+    }
+    const storage = await this.contract.storage_balance_of({account_id: this.accountId})
+    this.storageBalance = storage ? toNear(storage.available) : 0
+  }
+
+  async storageDeposit(amount: string): Promise<void> {
+    await this.contract.storage_deposit(
+      {},
+      '300000000000000',
+      parseNearAmount(amount),
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
   }
 
   async updateMemberList(): Promise<void> {
     this.memberList = await this.contract.member_list()
+    this.memberProfileList = await this.contract.member_profiles({})
+  }
+
+  async payDues(): Promise<void> {
+    const policy = await this.contract.policy()
+    if (!policy.dues) {
+      throw new SocietyError('ERR_DUES_DISABLED', ERROR_MESSAGES.ERR_DUES_DISABLED)
+    }
+    await this.contract.pay_dues(
+      {},
+      '300000000000000',
+      policy.dues.amount,
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
+  }
+
+  async updateProfile(name: string, avatar: string, bio: string, links: string[]): Promise<void> {
+    await this.contract.update_profile(
+      {
+        name,
+        avatar,
+        bio,
+        links,
+      },
+      '300000000000000',
+      '0',
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
   }
 
   async updateProposalList(): Promise<void> {
@@ -155,7 +361,9 @@ export class WalletService {
       },
       '300000000000000',
       '0',
-    )
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
   }
 
   async voteApprove(proposal_id: number): Promise<void> {
@@ -165,7 +373,9 @@ export class WalletService {
       },
       '300000000000000',
       '0',
-    )
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
   }
 
   async updateStatusMember(account_id: string): Promise<boolean> {
@@ -185,25 +395,37 @@ export class WalletService {
     )
   }
 
-  addMemberProposal(title: string, description: string): Promise<number> {
+  async addMemberProposal(title: string, description: string, attachments: Attachment[] = []): Promise<number> {
+    const policy = await this.contract.policy()
     return this.contract.add_member_proposal(
       {
         title,
         description,
+        attachments,
       },
-    )
+      '300000000000000',
+      policy.proposal_bond,
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
   }
 
-  addFundProposal(title: string, description: string, fund: string): Promise<number> {
+  async addFundProposal(title: string, description: string, fund: string, attachments: Attachment[] = []): Promise<number> {
+    const policy = await this.contract.policy()
     return this.contract.add_fund_proposal(
       {
         title,
         description,
         script: JSON.stringify({
           fund: parseNearAmount(fund)
-        })
+        }),
+        attachments,
       },
-    )
+      '300000000000000',
+      policy.proposal_bond,
+    ).catch(reason => {
+      throw toSocietyError(reason)
+    })
   }
 
   signIn(): void {
@@ -1,18 +1,29 @@
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
 use near_sdk::collections::Vector;
-use near_sdk::collections::{LookupMap, UnorderedSet};
+use near_sdk::collections::{LookupMap, UnorderedMap, UnorderedSet};
 use near_sdk::env;
+use near_sdk::ext_contract;
+use near_sdk::json_types::Base64VecU8;
 use near_sdk::json_types::ValidAccountId;
 use near_sdk::json_types::U128;
+use near_sdk::json_types::U64;
 use near_sdk::near_bindgen;
+use near_sdk::serde::de::DeserializeOwned;
 use near_sdk::serde::Deserialize;
 use near_sdk::serde::Serialize;
+use near_sdk::serde_json::json;
 use near_sdk::AccountId;
 use near_sdk::Balance;
 use near_sdk::BorshStorageKey;
 use near_sdk::CryptoHash;
+use near_sdk::Gas;
 use near_sdk::PanicOnDefault;
 use near_sdk::Promise;
+use near_sdk::PromiseOrValue;
+use near_sdk::PromiseResult;
+use near_sdk::StorageUsage;
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::option::Option;
 
 near_sdk::setup_alloc!();
@@ -21,26 +32,521 @@ near_sdk::setup_alloc!();
 const PARAM_VOTE_TARGET: f64 = 0.50; // 50%
 const PARAM_TIME_LOCK: u64 = 10 * 60 * 1_000_000_000; // 10m in nanoseconds
 const PARAM_FUND_RESERVE: Balance = 10_000_000_000_000_000_000_000_000; // reserve is 10NEAR
+const PARAM_PROPOSAL_BOND: Balance = 100_000_000_000_000_000_000_000; // bond is 0.1NEAR
+const PARAM_PROPOSAL_LIMIT: u64 = 3; // open proposals of one kind per author
+const PARAM_BOUNTY_BOND: Balance = 1_000_000_000_000_000_000_000_000; // bond is 1NEAR
+const PARAM_NFT_SPEC: &str = "nft-1.0.0";
+const PARAM_NFT_NAME: &str = "CryptoArt DAO";
+const PARAM_NFT_SYMBOL: &str = "CADAO";
+const PARAM_ART_COPIES_MAX: u64 = 100;
+const PARAM_ROYALTY_MAX: u32 = 5_000; // 50% in basis points
+const PARAM_ROYALTY_LEN_MAX: usize = 10;
+const PARAM_EXHIBITION_ITEMS_MAX: usize = 100;
+const PARAM_ATTACHMENT_MAX: usize = 10;
+const PARAM_ATTACHMENT_URI_LEN_MAX: usize = 256;
+const PARAM_ATTACHMENT_LABEL_LEN_MAX: usize = 100;
+const PARAM_COMMENT_LEN_MAX: u64 = 500;
+const PARAM_COMMENT_MAX: u64 = 100; // comments per proposal
+const PARAM_PROFILE_NAME_LEN_MAX: usize = 64;
+const PARAM_PROFILE_BIO_LEN_MAX: usize = 500;
+const PARAM_PROFILE_LINK_MAX: usize = 5;
+const PARAM_PROFILE_LINK_LEN_MAX: usize = 256;
+const PARAM_MARKET_METHOD: &str = "buy";
+const PARAM_ACQUISITION_TIMEOUT: u64 = 7 * 24 * 60 * 60 * 1_000_000_000; // 7d in nanoseconds
+const PARAM_AUCTION_DURATION_MAX: u64 = 90 * 24 * 60 * 60 * 1_000_000_000; // 90d in nanoseconds
+const PARAM_DUES_PERIOD_MAX: u64 = 366 * 24 * 60 * 60 * 1_000_000_000; // 366d in nanoseconds
+const PARAM_GAS_NFT_VIEW: Gas = 10_000_000_000_000; // 10TGas
+const PARAM_GAS_NFT_TRANSFER: Gas = 20_000_000_000_000; // 20TGas
+const PARAM_GAS_MARKET: Gas = 100_000_000_000_000; // 100TGas
+const PARAM_GAS_STAKING_POOL: Gas = 50_000_000_000_000; // 50TGas
+const PARAM_GAS_NFT_ON_TRANSFER: Gas = 25_000_000_000_000; // 25TGas
+const PARAM_GAS_NFT_ON_APPROVE: Gas = 25_000_000_000_000; // 25TGas
+const PARAM_GAS_CALLBACK: Gas = 10_000_000_000_000; // 10TGas
+const PARAM_EVENT_STANDARD: &str = "cryptoartdao";
+const PARAM_EVENT_VERSION: &str = "1.0.0";
+const PARAM_STORAGE_ACCOUNT: StorageUsage = 200; // registration record in bytes
+const PARAM_STORAGE_PROPOSAL: StorageUsage = 2_000; // proposal with a full title and description
+
+#[ext_contract(ext_staking_pool)]
+pub trait StakingPool {
+    fn deposit_and_stake(&mut self);
+    fn unstake(&mut self, amount: U128);
+    fn withdraw(&mut self, amount: U128);
+}
+
+#[ext_contract(ext_nft)]
+pub trait NonFungibleToken {
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token>;
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    );
+}
+
+#[ext_contract(ext_nft_receiver)]
+pub trait NonFungibleTokenReceiver {
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: String,
+        msg: String,
+    ) -> bool;
+}
+
+#[ext_contract(ext_nft_approval_receiver)]
+pub trait NonFungibleTokenApprovalReceiver {
+    fn nft_on_approve(
+        &mut self,
+        token_id: String,
+        owner_id: AccountId,
+        approval_id: u64,
+        msg: String,
+    );
+}
+
+#[ext_contract(ext_self)]
+pub trait SelfCallback {
+    fn on_staking_pool(&mut self, pool_id: AccountId, kind: ProposalKind, amount: U128);
+    fn on_acquire_nft(&mut self, acquisition_id: u64);
+    fn on_settle_auction(&mut self, auction_id: u64);
+    fn on_member_nft(&mut self, application: MemberApplication) -> Option<u64>;
+    fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: String,
+        approved_account_ids: HashMap<AccountId, u64>,
+    ) -> bool;
+}
 
 fn consensus(max: u64, quorum: u64) -> bool {
     let target = (max as f64 * PARAM_VOTE_TARGET).floor() as u64 + 1;
     quorum >= target
 }
 
-pub fn refund_deposit(storage_used: u64) {
-    let required_cost = env::storage_byte_cost() * Balance::from(storage_used);
-    let attached_deposit = env::attached_deposit();
+/// Log the NEP-297 event for indexers
+fn log_event(event: &str, data: serde_json::Value) {
+    let event = json!({
+        "standard": PARAM_EVENT_STANDARD,
+        "version": PARAM_EVENT_VERSION,
+        "event": event,
+        "data": [data],
+    });
+    env::log(format!("EVENT_JSON:{}", event).as_bytes());
+}
 
-    assert!(
-        required_cost <= attached_deposit,
-        "Must attach {} yoctoNEAR to cover storage",
-        required_cost,
-    );
+/// Failure of an entrypoint, the code is stable for clients and the message is for humans
+#[derive(Debug, PartialEq)]
+pub enum SocietyError {
+    AlreadyInitialized,
+    InitialMemberRequired,
+    OnlyMember,
+    OnlyReceiver,
+    OnlyClaimant,
+    OnlyCurator,
+    OnlyTokenOwner,
+    AlreadyMember(AccountId),
+    NotMember(AccountId),
+    LastMember,
+    InvalidAccount(AccountId),
+    InvalidScript(String),
+    InvalidField(&'static str, String),
+    FieldLength(&'static str, usize),
+    ZeroValue(&'static str),
+    OutOfBounds,
+    NotEnoughFund,
+    NotEnoughStaked,
+    NotEnoughUnstaking,
+    BondRequired(Balance),
+    OneYoctoRequired,
+    NotRegistered(AccountId),
+    RegistrationDeposit(Balance),
+    StorageDeposit(Balance),
+    StorageWithdraw(Balance),
+    CallbackResult,
+    UnexpectedKind,
+    ProposalNotFound,
+    ProposalNotActive(u64),
+    ProposalLocked(u64),
+    ProposalKindMismatch(u64),
+    ProposalLimit(u64),
+    ProposalFinalized(u64),
+    ProposalNotInVote(u64),
+    ProposalNotDraft(u64),
+    ProposalNotFinalized(u64),
+    ProposalArchived(u64),
+    ProposalNoScript,
+    ActionRequired,
+    MilestoneRequired,
+    AlreadyVoted,
+    StreamNotFound,
+    StreamSchedule,
+    StreamCliff,
+    StreamCanceled,
+    NothingToClaim,
+    GrantNotFound,
+    GrantPaid,
+    MilestoneNotFound,
+    MilestoneNotPending,
+    MilestoneNotReview,
+    BountyNotFound,
+    BountyClaimed,
+    BountyDone,
+    BountyExpired,
+    BountyNoSubmission,
+    TokenExists(String),
+    TokenNotFound,
+    TokenNotTransferable,
+    TokenSameOwner,
+    TokenNotOwned,
+    NotApproved,
+    PayoutTooLong,
+    MarketNotConfigured,
+    AcquisitionNotFound,
+    AcquisitionPending,
+    AuctionNotFound,
+    AuctionOpen,
+    AuctionClosed,
+    AuctionSettled,
+    AuctionNotFinished,
+    AuctionDuration(u64),
+    BidTooLow(Balance),
+    ExhibitionNotFound,
+    ExhibitionSchedule,
+    ExhibitionOrder,
+    CommentNotFound,
+    CommentHidden,
+    CommentLength(u64),
+    CommentLimit(u64),
+    AttachmentLimit,
+    DuesDisabled,
+    DuesRequired(Balance),
+    DuesLapsed(AccountId),
+    DuesOverflow,
+    DuesPeriod(u64),
+}
+
+impl SocietyError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            SocietyError::AlreadyInitialized => "ERR_ALREADY_INITIALIZED",
+            SocietyError::InitialMemberRequired => "ERR_INITIAL_MEMBER_REQUIRED",
+            SocietyError::OnlyMember => "ERR_ONLY_MEMBER",
+            SocietyError::OnlyReceiver => "ERR_ONLY_RECEIVER",
+            SocietyError::OnlyClaimant => "ERR_ONLY_CLAIMANT",
+            SocietyError::OnlyCurator => "ERR_ONLY_CURATOR",
+            SocietyError::OnlyTokenOwner => "ERR_ONLY_TOKEN_OWNER",
+            SocietyError::AlreadyMember(_) => "ERR_ALREADY_MEMBER",
+            SocietyError::NotMember(_) => "ERR_NOT_MEMBER",
+            SocietyError::LastMember => "ERR_LAST_MEMBER",
+            SocietyError::InvalidAccount(_) => "ERR_INVALID_ACCOUNT",
+            SocietyError::InvalidScript(_) => "ERR_INVALID_SCRIPT",
+            SocietyError::InvalidField(_, _) => "ERR_INVALID_FIELD",
+            SocietyError::FieldLength(_, _) => "ERR_FIELD_LENGTH",
+            SocietyError::ZeroValue(_) => "ERR_ZERO_VALUE",
+            SocietyError::OutOfBounds => "ERR_OUT_OF_BOUNDS",
+            SocietyError::NotEnoughFund => "ERR_NOT_ENOUGH_FUND",
+            SocietyError::NotEnoughStaked => "ERR_NOT_ENOUGH_STAKED",
+            SocietyError::NotEnoughUnstaking => "ERR_NOT_ENOUGH_UNSTAKING",
+            SocietyError::BondRequired(_) => "ERR_BOND_REQUIRED",
+            SocietyError::OneYoctoRequired => "ERR_ONE_YOCTO_REQUIRED",
+            SocietyError::NotRegistered(_) => "ERR_NOT_REGISTERED",
+            SocietyError::RegistrationDeposit(_) => "ERR_REGISTRATION_DEPOSIT",
+            SocietyError::StorageDeposit(_) => "ERR_STORAGE_DEPOSIT",
+            SocietyError::StorageWithdraw(_) => "ERR_STORAGE_WITHDRAW",
+            SocietyError::CallbackResult => "ERR_CALLBACK_RESULT",
+            SocietyError::UnexpectedKind => "ERR_UNEXPECTED_KIND",
+            SocietyError::ProposalNotFound => "ERR_PROPOSAL_NOT_FOUND",
+            SocietyError::ProposalNotActive(_) => "ERR_PROPOSAL_NOT_ACTIVE",
+            SocietyError::ProposalLocked(_) => "ERR_PROPOSAL_LOCKED",
+            SocietyError::ProposalKindMismatch(_) => "ERR_PROPOSAL_KIND_MISMATCH",
+            SocietyError::ProposalLimit(_) => "ERR_PROPOSAL_LIMIT",
+            SocietyError::ProposalFinalized(_) => "ERR_PROPOSAL_FINALIZED",
+            SocietyError::ProposalNotInVote(_) => "ERR_PROPOSAL_NOT_IN_VOTE",
+            SocietyError::ProposalNotDraft(_) => "ERR_PROPOSAL_NOT_DRAFT",
+            SocietyError::ProposalNotFinalized(_) => "ERR_PROPOSAL_NOT_FINALIZED",
+            SocietyError::ProposalArchived(_) => "ERR_PROPOSAL_ARCHIVED",
+            SocietyError::ProposalNoScript => "ERR_PROPOSAL_NO_SCRIPT",
+            SocietyError::ActionRequired => "ERR_ACTION_REQUIRED",
+            SocietyError::MilestoneRequired => "ERR_MILESTONE_REQUIRED",
+            SocietyError::AlreadyVoted => "ERR_ALREADY_VOTED",
+            SocietyError::StreamNotFound => "ERR_STREAM_NOT_FOUND",
+            SocietyError::StreamSchedule => "ERR_STREAM_SCHEDULE",
+            SocietyError::StreamCliff => "ERR_STREAM_CLIFF",
+            SocietyError::StreamCanceled => "ERR_STREAM_CANCELED",
+            SocietyError::NothingToClaim => "ERR_NOTHING_TO_CLAIM",
+            SocietyError::GrantNotFound => "ERR_GRANT_NOT_FOUND",
+            SocietyError::GrantPaid => "ERR_GRANT_PAID",
+            SocietyError::MilestoneNotFound => "ERR_MILESTONE_NOT_FOUND",
+            SocietyError::MilestoneNotPending => "ERR_MILESTONE_NOT_PENDING",
+            SocietyError::MilestoneNotReview => "ERR_MILESTONE_NOT_REVIEW",
+            SocietyError::BountyNotFound => "ERR_BOUNTY_NOT_FOUND",
+            SocietyError::BountyClaimed => "ERR_BOUNTY_CLAIMED",
+            SocietyError::BountyDone => "ERR_BOUNTY_DONE",
+            SocietyError::BountyExpired => "ERR_BOUNTY_EXPIRED",
+            SocietyError::BountyNoSubmission => "ERR_BOUNTY_NO_SUBMISSION",
+            SocietyError::TokenExists(_) => "ERR_TOKEN_EXISTS",
+            SocietyError::TokenNotFound => "ERR_TOKEN_NOT_FOUND",
+            SocietyError::TokenNotTransferable => "ERR_TOKEN_NOT_TRANSFERABLE",
+            SocietyError::TokenSameOwner => "ERR_TOKEN_SAME_OWNER",
+            SocietyError::TokenNotOwned => "ERR_TOKEN_NOT_OWNED",
+            SocietyError::NotApproved => "ERR_NOT_APPROVED",
+            SocietyError::PayoutTooLong => "ERR_PAYOUT_TOO_LONG",
+            SocietyError::MarketNotConfigured => "ERR_MARKET_NOT_CONFIGURED",
+            SocietyError::AcquisitionNotFound => "ERR_ACQUISITION_NOT_FOUND",
+            SocietyError::AcquisitionPending => "ERR_ACQUISITION_PENDING",
+            SocietyError::AuctionNotFound => "ERR_AUCTION_NOT_FOUND",
+            SocietyError::AuctionOpen => "ERR_AUCTION_OPEN",
+            SocietyError::AuctionClosed => "ERR_AUCTION_CLOSED",
+            SocietyError::AuctionSettled => "ERR_AUCTION_SETTLED",
+            SocietyError::AuctionNotFinished => "ERR_AUCTION_NOT_FINISHED",
+            SocietyError::AuctionDuration(_) => "ERR_AUCTION_DURATION",
+            SocietyError::BidTooLow(_) => "ERR_BID_TOO_LOW",
+            SocietyError::ExhibitionNotFound => "ERR_EXHIBITION_NOT_FOUND",
+            SocietyError::ExhibitionSchedule => "ERR_EXHIBITION_SCHEDULE",
+            SocietyError::ExhibitionOrder => "ERR_EXHIBITION_ORDER",
+            SocietyError::CommentNotFound => "ERR_COMMENT_NOT_FOUND",
+            SocietyError::CommentHidden => "ERR_COMMENT_HIDDEN",
+            SocietyError::CommentLength(_) => "ERR_COMMENT_LENGTH",
+            SocietyError::CommentLimit(_) => "ERR_COMMENT_LIMIT",
+            SocietyError::AttachmentLimit => "ERR_ATTACHMENT_LIMIT",
+            SocietyError::DuesDisabled => "ERR_DUES_DISABLED",
+            SocietyError::DuesRequired(_) => "ERR_DUES_REQUIRED",
+            SocietyError::DuesLapsed(_) => "ERR_DUES_LAPSED",
+            SocietyError::DuesOverflow => "ERR_DUES_OVERFLOW",
+            SocietyError::DuesPeriod(_) => "ERR_DUES_PERIOD",
+        }
+    }
+
+    /// Abort the call with `CODE: message`
+    pub fn panic(&self) -> ! {
+        env::panic(format!("{}: {}", self.code(), self).as_bytes())
+    }
+}
+
+impl std::fmt::Display for SocietyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SocietyError::AlreadyInitialized => write!(f, "Already initialized"),
+            SocietyError::InitialMemberRequired => write!(f, "Need minimum one initial member"),
+            SocietyError::OnlyMember => write!(f, "Only for members"),
+            SocietyError::OnlyReceiver => write!(f, "Only for receiver"),
+            SocietyError::OnlyClaimant => write!(f, "Only for claimant"),
+            SocietyError::OnlyCurator => write!(f, "Only for curator"),
+            SocietyError::OnlyTokenOwner => write!(f, "Only for token owner"),
+            SocietyError::AlreadyMember(account_id) => {
+                write!(f, "Account {} already is member", account_id)
+            }
+            SocietyError::NotMember(account_id) => {
+                write!(f, "Account {} is not member", account_id)
+            }
+            SocietyError::LastMember => write!(f, "Cannot remove the last member"),
+            SocietyError::InvalidAccount(account_id) => {
+                write!(f, "Account {} is invalid", account_id)
+            }
+            SocietyError::InvalidScript(reason) => write!(f, "Script is invalid: {}", reason),
+            SocietyError::InvalidField(field, reason) => write!(f, "Field {} {}", field, reason),
+            SocietyError::FieldLength(field, max) => {
+                write!(f, "Field {} must be less {} length", field, max)
+            }
+            SocietyError::ZeroValue(field) => write!(f, "Cannot provide {} of 0.", field),
+            SocietyError::OutOfBounds => {
+                write!(f, "Out of bounds, please use a smaller from_index.")
+            }
+            SocietyError::NotEnoughFund => write!(f, "The fund does not have so many resources"),
+            SocietyError::NotEnoughStaked => {
+                write!(f, "The pool does not have so many staked resources")
+            }
+            SocietyError::NotEnoughUnstaking => {
+                write!(f, "The pool does not have so many unstaking resources")
+            }
+            SocietyError::BondRequired(bond) => write!(f, "Must attach {} yoctoNEAR as bond", bond),
+            SocietyError::OneYoctoRequired => {
+                write!(f, "Requires attached deposit of exactly 1 yoctoNEAR")
+            }
+            SocietyError::NotRegistered(account_id) => write!(
+                f,
+                "Account {} is not registered, call storage_deposit",
+                account_id
+            ),
+            SocietyError::RegistrationDeposit(min) => {
+                write!(f, "Must attach {} yoctoNEAR to register", min)
+            }
+            SocietyError::StorageDeposit(amount) => {
+                write!(f, "Must deposit {} yoctoNEAR more to cover storage", amount)
+            }
+            SocietyError::StorageWithdraw(available) => {
+                write!(f, "Only {} yoctoNEAR is available to withdraw", available)
+            }
+            SocietyError::CallbackResult => {
+                write!(f, "Contract expected a result on the callback")
+            }
+            SocietyError::UnexpectedKind => write!(f, "Unexpected proposal kind"),
+            SocietyError::ProposalNotFound => write!(f, "Proposal not found"),
+            SocietyError::ProposalNotActive(id) => write!(f, "Proposal {} not active", id),
+            SocietyError::ProposalLocked(id) => write!(f, "Proposal {} is locked, try later", id),
+            SocietyError::ProposalKindMismatch(id) => write!(
+                f,
+                "You can update the proposal {} only with the same kind",
+                id
+            ),
+            SocietyError::ProposalLimit(limit) => {
+                write!(f, "You can have only {} open proposals of this kind", limit)
+            }
+            SocietyError::ProposalFinalized(id) => {
+                write!(f, "Proposal {} is already finalized", id)
+            }
+            SocietyError::ProposalNotInVote(id) => {
+                write!(f, "Proposal {} is a draft, update it to vote again", id)
+            }
+            SocietyError::ProposalNotDraft(id) => {
+                write!(
+                    f,
+                    "You can update the proposal {} only in the draft status",
+                    id
+                )
+            }
+            SocietyError::ProposalNotFinalized(id) => write!(f, "Proposal {} is not finalized", id),
+            SocietyError::ProposalArchived(id) => write!(f, "Proposal {} is archived", id),
+            SocietyError::ProposalNoScript => write!(f, "Member request has no script to update"),
+            SocietyError::ActionRequired => write!(f, "Need minimum one action"),
+            SocietyError::MilestoneRequired => write!(f, "Need minimum one milestone"),
+            SocietyError::AlreadyVoted => write!(f, "You are already voted"),
+            SocietyError::StreamNotFound => write!(f, "Stream not found"),
+            SocietyError::StreamSchedule => write!(f, "Stream must end after start"),
+            SocietyError::StreamCliff => write!(f, "Stream cliff must be between start and end"),
+            SocietyError::StreamCanceled => write!(f, "Stream is already canceled"),
+            SocietyError::NothingToClaim => write!(f, "Nothing to claim"),
+            SocietyError::GrantNotFound => write!(f, "Grant not found"),
+            SocietyError::GrantPaid => write!(f, "Grant has no unpaid milestones"),
+            SocietyError::MilestoneNotFound => write!(f, "Milestone not found"),
+            SocietyError::MilestoneNotPending => {
+                write!(f, "You can submit the milestone only in the pending status")
+            }
+            SocietyError::MilestoneNotReview => {
+                write!(f, "You can vote the milestone only in the review status")
+            }
+            SocietyError::BountyNotFound => write!(f, "Bounty not found"),
+            SocietyError::BountyClaimed => write!(f, "Bounty is already claimed"),
+            SocietyError::BountyDone => write!(f, "Bounty is already done"),
+            SocietyError::BountyExpired => write!(f, "Bounty claim is expired"),
+            SocietyError::BountyNoSubmission => write!(f, "Submit the bounty work before"),
+            SocietyError::TokenExists(token_id) => write!(f, "Token {} already exists", token_id),
+            SocietyError::TokenNotFound => write!(f, "Token not found"),
+            SocietyError::TokenNotTransferable => write!(f, "Membership token is non-transferable"),
+            SocietyError::TokenSameOwner => {
+                write!(f, "The token owner and the receiver should be different")
+            }
+            SocietyError::TokenNotOwned => write!(f, "Token is not owned by the society"),
+            SocietyError::NotApproved => write!(f, "Sender is not approved for the token"),
+            SocietyError::PayoutTooLong => write!(f, "Market cannot payout to that many receivers"),
+            SocietyError::MarketNotConfigured => write!(f, "Market is not configured"),
+            SocietyError::AcquisitionNotFound => write!(f, "Acquisition not found"),
+            SocietyError::AcquisitionPending => write!(f, "Token acquisition is already pending"),
+            SocietyError::AuctionNotFound => write!(f, "Auction not found"),
+            SocietyError::AuctionOpen => write!(f, "Token auction is already open"),
+            SocietyError::AuctionClosed => write!(f, "Auction is closed"),
+            SocietyError::AuctionSettled => write!(f, "Auction is already settled"),
+            SocietyError::AuctionNotFinished => write!(f, "Auction is not finished"),
+            SocietyError::AuctionDuration(max) => {
+                write!(f, "Auction cannot run longer than {} nanoseconds", max)
+            }
+            SocietyError::BidTooLow(min) => {
+                write!(f, "Must attach minimum {} yoctoNEAR to bid", min)
+            }
+            SocietyError::ExhibitionNotFound => write!(f, "Exhibition not found"),
+            SocietyError::ExhibitionSchedule => write!(f, "Exhibition must close after open"),
+            SocietyError::ExhibitionOrder => write!(f, "Order must list every item once"),
+            SocietyError::CommentNotFound => write!(f, "Comment not found"),
+            SocietyError::CommentHidden => write!(f, "Comment is already hidden"),
+            SocietyError::CommentLength(max) => {
+                write!(f, "Comment must be from 1 to {} bytes", max)
+            }
+            SocietyError::CommentLimit(max) => {
+                write!(f, "Proposal cannot have more than {} comments", max)
+            }
+            SocietyError::AttachmentLimit => write!(
+                f,
+                "Proposal cannot have more than {} attachments",
+                PARAM_ATTACHMENT_MAX
+            ),
+            SocietyError::DuesDisabled => write!(f, "Dues are not enabled by the policy"),
+            SocietyError::DuesRequired(amount) => {
+                write!(f, "Must attach minimum {} yoctoNEAR as dues", amount)
+            }
+            SocietyError::DuesLapsed(account_id) => write!(
+                f,
+                "Dues of the account {} are lapsed, call pay_dues",
+                account_id
+            ),
+            SocietyError::DuesOverflow => write!(f, "Deposit covers too many dues periods"),
+            SocietyError::DuesPeriod(max) => {
+                write!(f, "Dues period and grace cannot exceed {} nanoseconds", max)
+            }
+        }
+    }
+}
+
+/// Abort with the error unless the condition holds
+fn require(condition: bool, error: SocietyError) {
+    if !condition {
+        error.panic()
+    }
+}
+
+/// Parse a proposal script, a malformed one is `ERR_INVALID_SCRIPT` instead of a bare unwrap
+fn parse_script<T: DeserializeOwned>(script: &str) -> T {
+    match serde_json::from_str(script) {
+        Ok(value) => value,
+        Err(error) => SocietyError::InvalidScript(error.to_string()).panic(),
+    }
+}
 
-    let refund = attached_deposit - required_cost;
-    if refund > 1 {
-        Promise::new(env::predecessor_account_id()).transfer(refund);
+fn assert_proposal_text(title: &str, description: &str) {
+    if title.len() > 170 {
+        SocietyError::FieldLength("title", 170).panic()
     }
+    if description.len() > 1000 {
+        SocietyError::FieldLength("description", 1000).panic()
+    }
+}
+
+fn storage_cost(storage_used: StorageUsage) -> Balance {
+    env::storage_byte_cost() * Balance::from(storage_used)
+}
+
+/// Bytes of an approval entry in the token, paid by the owner
+fn approval_storage(account_id: &str) -> StorageUsage {
+    account_id.len() as StorageUsage + 4 + 8
+}
+
+fn is_promise_success() -> bool {
+    require(
+        env::promise_results_count() == 1,
+        SocietyError::CallbackResult,
+    );
+    matches!(env::promise_result(0), PromiseResult::Successful(_))
+}
+
+fn member_token_id(account_id: &str) -> String {
+    format!("member:{}", account_id)
+}
+
+fn nft_key(nft_contract_id: &str, token_id: &str) -> String {
+    format!("{}:{}", nft_contract_id, token_id)
+}
+
+fn is_member_token(token_id: &str) -> bool {
+    token_id.starts_with("member:")
 }
 
 pub fn hash(data: String) -> CryptoHash {
@@ -54,549 +560,6917 @@ struct FundScript {
     fund: U128,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Deserialize, PartialEq)]
-#[serde(crate = "near_sdk::serde")]
-pub enum ProposalKind {
-    MemberRequest,
-    FundRequest,
+#[derive(Deserialize)]
+struct FunctionCallAction {
+    method_name: String,
+    args: Base64VecU8,
+    deposit: U128,
+    gas: U64,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Deserialize, PartialEq)]
-#[serde(crate = "near_sdk::serde")]
-pub enum ProposalStatus {
-    Draft,
-    Vote,
-    Accepted,
-    Rejected,
+#[derive(Deserialize)]
+struct FunctionCallScript {
+    receiver_id: ValidAccountId,
+    actions: Vec<FunctionCallAction>,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+impl FunctionCallScript {
+    fn deposit(&self) -> Balance {
+        self.actions
+            .iter()
+            .map(|action| u128::from(action.deposit))
+            .sum()
+    }
+
+    fn promise(self) -> Promise {
+        let mut promise = Promise::new(self.receiver_id.into());
+        for action in self.actions {
+            promise = promise.function_call(
+                action.method_name.into_bytes(),
+                action.args.into(),
+                action.deposit.into(),
+                Gas::from(action.gas),
+            );
+        }
+        promise
+    }
+}
+
+#[derive(Deserialize)]
+struct StakeScript {
+    pool_id: ValidAccountId,
+    amount: U128,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct StakeState {
+    staked: Balance,
+    unstaking: Balance,
+}
+
+#[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
-pub struct ProposalVote {
-    approve: u64,
-    reject: u64,
+pub struct Stake {
+    pool_id: AccountId,
+    staked: U128,
+    unstaking: U128,
 }
 
-impl ProposalVote {
-    pub fn is_approve(&self) -> bool {
-        self.approve > self.reject
-    }
+#[derive(Deserialize)]
+struct StreamScript {
+    total: U128,
+    start: U64,
+    end: U64,
+    cliff: Option<U64>,
+}
 
-    pub fn is_parte(&self) -> bool {
-        self.approve == self.reject
+#[derive(Deserialize)]
+struct StreamCancelScript {
+    stream_id: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct StreamState {
+    proposal_id: u64,
+    receiver: AccountId,
+    total: Balance,
+    claimed: Balance,
+    start: u64,
+    end: u64,
+    cliff: u64,
+    canceled: Option<u64>,
+}
+
+impl StreamState {
+    /// Amount vested at the timestamp, cancellation stops vesting
+    fn vested(&self, timestamp: u64) -> Balance {
+        let timestamp = match self.canceled {
+            Some(canceled) => timestamp.min(canceled),
+            None => timestamp,
+        };
+        if timestamp < self.cliff {
+            0
+        } else if timestamp >= self.end {
+            self.total
+        } else {
+            // split the division so the product stays in u128 for any total
+            let elapsed = Balance::from(timestamp - self.start);
+            let duration = Balance::from(self.end - self.start);
+            self.total / duration * elapsed + self.total % duration * elapsed / duration
+        }
     }
 
-    pub fn is_reject(&self) -> bool {
-        self.approve < self.reject
+    fn claimable(&self) -> Balance {
+        self.vested(env::block_timestamp()) - self.claimed
     }
 }
 
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
-pub struct Proposal {
+pub struct Stream {
     id: u64,
-    timestamp: u64,
+    proposal_id: u64,
+    receiver: AccountId,
+    total: U128,
+    claimed: U128,
+    claimable: U128,
+    start: U64,
+    end: U64,
+    cliff: U64,
+    canceled: Option<U64>,
+}
+
+#[derive(Deserialize)]
+struct MilestoneScript {
     title: String,
-    kind: ProposalKind,
-    status: ProposalStatus,
-    description: String,
-    script: Option<String>,
-    author: AccountId,
-    vote: ProposalVote,
+    amount: U128,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[derive(Deserialize)]
+struct MilestoneGrantScript {
+    milestones: Vec<MilestoneScript>,
+}
+
+impl MilestoneGrantScript {
+    fn total(&self) -> Balance {
+        self.milestones
+            .iter()
+            .map(|milestone| u128::from(milestone.amount))
+            .sum()
+    }
+}
+
+#[derive(Deserialize)]
+struct GrantCancelScript {
+    grant_id: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Clone)]
 #[serde(crate = "near_sdk::serde")]
-pub struct ProposalState {
-    timestamp: u64,
+pub enum MilestoneStatus {
+    Pending,
+    Review,
+    Paid,
+    Canceled,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Milestone {
     title: String,
-    kind: ProposalKind,
-    status: ProposalStatus,
-    description: String,
-    script: Option<String>,
-    author: AccountId,
+    amount: U128,
+    status: MilestoneStatus,
+    deliverable: Option<String>,
     vote: ProposalVote,
 }
 
-impl ProposalState {
-    pub fn new(
-        title: Option<String>,
-        description: Option<String>,
-        author: AccountId,
-        kind: ProposalKind,
-        status: ProposalStatus,
-        script: Option<String>,
-    ) -> Self {
-        let title = title.unwrap_or_default();
-        if title.len() > 170 {
-            env::panic(b"Field title mus be less 70 lenght")
-        }
-        let description = description.unwrap_or_default();
-        if description.len() > 1000 {
-            env::panic(b"Field description mus be less 1000 lenght")
-        }
-        Self {
-            timestamp: env::block_timestamp(),
-            title,
-            kind,
-            status,
-            description,
-            script,
-            author,
-            vote: ProposalVote {
-                approve: 0,
-                reject: 0,
-            },
-        }
-    }
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct GrantState {
+    proposal_id: u64,
+    receiver: AccountId,
+    milestones: Vec<Milestone>,
+}
 
-    fn is_draft(&self) -> bool {
-        self.status == ProposalStatus::Draft
-    }
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Grant {
+    id: u64,
+    proposal_id: u64,
+    receiver: AccountId,
+    milestones: Vec<Milestone>,
+}
 
-    fn is_accepted(&self) -> bool {
-        self.status == ProposalStatus::Accepted
-    }
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct Policy {
+    proposal_bond: U128,
+    bounty_bond: U128,
+    bounty_forfeit: bool,
+    market_id: Option<AccountId>,
+    market_method: String,
+    member_nft_contract_id: Option<AccountId>,
+    proposal_limit: u64,
+    kind_limit: Vec<KindLimit>,
+    comment_open: bool,
+    /// Comments allowed per proposal
+    comment_limit: u64,
+    /// Comment text length in bytes
+    comment_len_max: u64,
+    dues: Option<DuesPolicy>,
+}
 
-    fn vote(&mut self, resolve: bool, max: u64) {
-        if resolve {
-            self.vote.approve += 1;
-        } else {
-            self.vote.reject += 1;
+impl Policy {
+    /// Open proposals of the kind allowed per author
+    fn proposal_limit(&self, kind: &ProposalKind) -> u64 {
+        match self.kind_limit.iter().find(|limit| &limit.kind == kind) {
+            Some(limit) => limit.limit,
+            None => self.proposal_limit,
         }
-        self.calc(max);
     }
+}
 
-    fn calc(&mut self, total: u64) {
-        if !self.consensus(total) {
-            return;
-        }
-        if self.vote.is_approve() {
-            self.status = ProposalStatus::Accepted;
-        } else if self.vote.is_reject() {
-            self.status = ProposalStatus::Rejected;
-        } else if self.quorum() == total {
-            self.status = ProposalStatus::Draft;
-            self.vote.reject = 0;
-            self.vote.approve = 0;
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            proposal_bond: U128(PARAM_PROPOSAL_BOND),
+            bounty_bond: U128(PARAM_BOUNTY_BOND),
+            bounty_forfeit: true,
+            market_id: None,
+            market_method: PARAM_MARKET_METHOD.to_string(),
+            member_nft_contract_id: None,
+            proposal_limit: PARAM_PROPOSAL_LIMIT,
+            kind_limit: vec![KindLimit {
+                kind: ProposalKind::MemberRequest,
+                limit: 1,
+            }],
+            comment_open: false,
+            comment_limit: PARAM_COMMENT_MAX,
+            comment_len_max: PARAM_COMMENT_LEN_MAX,
+            dues: None,
         }
     }
+}
 
-    fn consensus(&self, max: u64) -> bool {
-        consensus(max, self.quorum())
-    }
+/// Members pay `amount` per `period`, voting rights lapse `grace` after the paid-through date
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct DuesPolicy {
+    amount: U128,
+    period: U64,
+    grace: U64,
+}
 
-    fn quorum(&self) -> u64 {
-        self.vote.approve + self.vote.reject
-    }
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MemberStanding {
+    account_id: AccountId,
+    paid_through: Option<U64>,
+    lapsed: bool,
 }
 
-#[derive(BorshSerialize, BorshStorageKey)]
-enum StorageKey {
-    MemberList,
-    ProposalList,
-    ProposalVote { hash: CryptoHash },
-    VoteList,
-    ActiveProposal,
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct KindLimit {
+    kind: ProposalKind,
+    limit: u64,
 }
 
-#[near_bindgen]
-#[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
-pub struct Society {
-    member_list: UnorderedSet<AccountId>,
-    proposal_list: Vector<ProposalState>,
-    vote_list: LookupMap<u64, UnorderedSet<AccountId>>,
-    active_proposal: LookupMap<AccountId, u64>,
-    fund_proposal: Balance,
+#[derive(Deserialize)]
+struct NftTokenId {
+    token_id: String,
 }
 
-#[near_bindgen]
-impl Society {
-    #[init]
-    pub fn init(initial_members: Vec<ValidAccountId>) -> Self {
-        assert!(!env::state_exists(), "Already initialized");
-        assert!(
-            !initial_members.is_empty(),
-            "Need minimum one initial member"
-        );
-        let mut contract = Self::new();
-        contract.setup(initial_members);
-        contract
-    }
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MemberApplication {
+    account_id: AccountId,
+    title: Option<String>,
+    description: Option<String>,
+    bond: U128,
+    proposal_id: Option<u64>,
+    attachments: Option<Vec<Attachment>>,
+}
 
-    fn new() -> Self {
-        Self {
-            member_list: UnorderedSet::new(StorageKey::MemberList),
-            proposal_list: Vector::new(StorageKey::ProposalList),
-            vote_list: LookupMap::new(StorageKey::VoteList),
-            active_proposal: LookupMap::new(StorageKey::ActiveProposal),
-            fund_proposal: 0,
+#[derive(Serialize)]
+#[serde(crate = "near_sdk::serde")]
+struct MemberScript {
+    nft_contract_id: AccountId,
+    token_id: String,
+}
+
+#[derive(Deserialize)]
+struct AcquireNftScript {
+    nft_contract_id: ValidAccountId,
+    token_id: String,
+    price: U128,
+}
+
+#[derive(Deserialize)]
+struct AuctionScript {
+    nft_contract_id: Option<ValidAccountId>,
+    token_id: String,
+    reserve_price: U128,
+    min_increment: U128,
+    duration: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bid {
+    account_id: AccountId,
+    amount: U128,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Auction {
+    proposal_id: u64,
+    nft_contract_id: AccountId,
+    token_id: String,
+    reserve_price: U128,
+    min_increment: U128,
+    end: U64,
+    bid: Option<Bid>,
+    settled: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ExhibitionItem {
+    nft_contract_id: AccountId,
+    token_id: String,
+}
+
+#[derive(Deserialize)]
+struct ExhibitionScript {
+    title: String,
+    description: String,
+    curator: ValidAccountId,
+    items: Vec<ExhibitionItem>,
+    open: U64,
+    close: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ExhibitionState {
+    proposal_id: u64,
+    title: String,
+    description: String,
+    curator: AccountId,
+    items: Vec<ExhibitionItem>,
+    open: u64,
+    close: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Exhibition {
+    id: u64,
+    proposal_id: u64,
+    title: String,
+    description: String,
+    curator: AccountId,
+    items: u64,
+    open: U64,
+    close: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AcquisitionStatus {
+    Pending,
+    Acquired,
+    Failed,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Acquisition {
+    proposal_id: u64,
+    nft_contract_id: AccountId,
+    token_id: String,
+    price: U128,
+    status: AcquisitionStatus,
+    timestamp: U64,
+}
+
+#[derive(Deserialize)]
+struct BountyScript {
+    reward: U128,
+    /// Time a claimant has to submit the work, in nanoseconds from the claim
+    deadline: U64,
+}
+
+#[derive(Deserialize)]
+struct BountyDoneScript {
+    bounty_id: U64,
+}
+
+#[derive(Deserialize)]
+struct BountyCancelScript {
+    bounty_id: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BountyClaim {
+    account_id: AccountId,
+    timestamp: U64,
+    bond: U128,
+    submission: Option<String>,
+    proposal_id: Option<u64>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct BountyState {
+    proposal_id: u64,
+    reward: U128,
+    /// Claim duration in nanoseconds, not an absolute date
+    deadline: U64,
+    claim: Option<BountyClaim>,
+    done: bool,
+    canceled: bool,
+}
+
+impl BountyState {
+    fn is_expired(&self, claim: &BountyClaim) -> bool {
+        claim.timestamp.0.saturating_add(self.deadline.0) < env::block_timestamp()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Bounty {
+    id: u64,
+    proposal_id: u64,
+    reward: U128,
+    deadline: U64,
+    claim: Option<BountyClaim>,
+    done: bool,
+    canceled: bool,
+}
+
+#[derive(Deserialize)]
+struct CommentHideScript {
+    proposal_id: U64,
+    comment_id: U64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CommentState {
+    author: AccountId,
+    timestamp: u64,
+    text: String,
+    reply_to: Option<u64>,
+    hidden: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Comment {
+    id: u64,
+    author: AccountId,
+    timestamp: U64,
+    text: Option<String>,
+    reply_to: Option<u64>,
+    hidden: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct MemberProfileState {
+    name: String,
+    avatar: Option<String>,
+    bio: String,
+    links: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MemberProfile {
+    account_id: AccountId,
+    name: String,
+    avatar: Option<String>,
+    bio: String,
+    links: Vec<String>,
+    joined_at: U64,
+    admitted_by_proposal: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct MemberRemoveScript {
+    account_id: ValidAccountId,
+}
+
+#[derive(Deserialize)]
+struct ArtMintScript {
+    title: String,
+    description: Option<String>,
+    media: String,
+    media_hash: Base64VecU8,
+    copies: Option<u64>,
+    receiver_id: Option<ValidAccountId>,
+    royalty: Option<HashMap<AccountId, u32>>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, Default)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadata {
+    title: Option<String>,
+    description: Option<String>,
+    media: Option<String>,
+    media_hash: Option<Base64VecU8>,
+    copies: Option<u64>,
+    issued_at: Option<String>,
+    expires_at: Option<String>,
+    starts_at: Option<String>,
+    updated_at: Option<String>,
+    extra: Option<String>,
+    reference: Option<String>,
+    reference_hash: Option<Base64VecU8>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NFTContractMetadata {
+    spec: String,
+    name: String,
+    symbol: String,
+    icon: Option<String>,
+    base_uri: Option<String>,
+    reference: Option<String>,
+    reference_hash: Option<Base64VecU8>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct TokenState {
+    owner_id: AccountId,
+    metadata: TokenMetadata,
+    royalty: HashMap<AccountId, u32>,
+    approved_account_ids: HashMap<AccountId, u64>,
+    next_approval_id: u64,
+}
+
+impl TokenState {
+    /// Split the balance between royalty receivers in basis points, the rest goes to the owner
+    fn payout(&self, balance: Balance) -> HashMap<AccountId, U128> {
+        let mut payout: HashMap<AccountId, U128> = HashMap::new();
+        let mut rest = balance;
+        for (account_id, royalty) in self.royalty.iter() {
+            if account_id == &self.owner_id {
+                continue;
+            }
+            let amount = balance * Balance::from(*royalty) / 10_000;
+            rest -= amount;
+            payout.insert(account_id.clone(), U128(amount));
+        }
+        payout.insert(self.owner_id.clone(), U128(rest));
+        payout
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Payout {
+    payout: HashMap<AccountId, U128>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Token {
+    token_id: String,
+    owner_id: AccountId,
+    metadata: Option<TokenMetadata>,
+    approved_account_ids: HashMap<AccountId, u64>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Default)]
+pub struct StorageState {
+    total: Balance,
+    used: StorageUsage,
+}
+
+impl StorageState {
+    pub fn available(&self) -> Balance {
+        self.total - storage_cost(self.used)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalance {
+    total: U128,
+    available: U128,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct StorageBalanceBounds {
+    min: U128,
+    max: Option<U128>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct FundBalance {
+    liquid: U128,
+    staked: U128,
+    unstaking: U128,
+    royalty: U128,
+}
+
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Deserialize, PartialEq, Clone,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalKind {
+    MemberRequest,
+    FundRequest,
+    FunctionCall,
+    StakeRequest,
+    UnstakeRequest,
+    WithdrawRequest,
+    StreamRequest,
+    StreamCancel,
+    MilestoneGrant,
+    GrantCancel,
+    Bounty,
+    BountyDone,
+    BountyCancel,
+    PolicyUpdate,
+    MemberRemove,
+    ArtMint,
+    AcquireNft,
+    AuctionArtwork,
+    Exhibition,
+    CommentHide,
+}
+
+#[derive(
+    BorshSerialize, BorshDeserialize, BorshStorageKey, Serialize, Deserialize, PartialEq, Clone,
+)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalStatus {
+    Draft,
+    Vote,
+    Accepted,
+    Rejected,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalVote {
+    approve: u64,
+    reject: u64,
+    spam: u64,
+}
+
+impl ProposalVote {
+    pub fn is_approve(&self) -> bool {
+        self.approve > self.reject
+    }
+
+    pub fn is_parte(&self) -> bool {
+        self.approve == self.reject
+    }
+
+    pub fn is_reject(&self) -> bool {
+        self.approve < self.reject
+    }
+
+    /// Most of the reject votes marked the proposal as spam
+    pub fn is_spam(&self) -> bool {
+        self.spam > self.reject - self.spam
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Proposal {
+    id: u64,
+    timestamp: u64,
+    title: String,
+    kind: ProposalKind,
+    status: ProposalStatus,
+    description: String,
+    script: Option<String>,
+    author: AccountId,
+    vote: ProposalVote,
+    bond: U128,
+    archived: bool,
+    attachments: Vec<Attachment>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalRevision {
+    timestamp: U64,
+    title: String,
+    description: String,
+    script: Option<String>,
+    attachments: Vec<Attachment>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Attachment {
+    uri: String,
+    hash: Base64VecU8,
+    mime_type: String,
+    label: String,
+}
+
+impl Attachment {
+    /// CIDv0 in base58 or CIDv1 in base32 with a sha2-256 multihash
+    fn is_cid(uri: &str) -> bool {
+        (uri.starts_with("Qm") && uri.len() == 46 && uri.chars().all(|c| c.is_ascii_alphanumeric()))
+            || (uri.starts_with("bafy")
+                && uri.len() == 59
+                && uri
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || ('2'..='7').contains(&c)))
+    }
+
+    fn is_mime_type(mime_type: &str) -> bool {
+        let part_list: Vec<&str> = mime_type.splitn(2, '/').collect();
+        part_list.len() == 2
+            && part_list.iter().all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c))
+            })
+    }
+
+    fn assert_valid(&self) {
+        if self.uri.len() > PARAM_ATTACHMENT_URI_LEN_MAX
+            || !(self.uri.starts_with("ipfs://")
+                || self.uri.starts_with("https://")
+                || Self::is_cid(&self.uri))
+        {
+            SocietyError::InvalidField(
+                "attachment uri",
+                format!(
+                    "must be an IPFS CID, ipfs:// or https:// link up to {} bytes",
+                    PARAM_ATTACHMENT_URI_LEN_MAX
+                ),
+            )
+            .panic()
+        }
+        if self.hash.0.len() != 32 {
+            SocietyError::InvalidField("attachment hash", "must be sha256".to_string()).panic()
+        }
+        if !Self::is_mime_type(&self.mime_type) {
+            SocietyError::InvalidField("attachment mime type", "must be type/subtype".to_string())
+                .panic()
         }
+        if self.label.len() > PARAM_ATTACHMENT_LABEL_LEN_MAX {
+            SocietyError::FieldLength("attachment label", PARAM_ATTACHMENT_LABEL_LEN_MAX).panic()
+        }
+    }
+}
+
+fn assert_attachments(attachments: &[Attachment]) {
+    require(
+        attachments.len() <= PARAM_ATTACHMENT_MAX,
+        SocietyError::AttachmentLimit,
+    );
+    for attachment in attachments {
+        attachment.assert_valid();
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "near_sdk::serde", default)]
+pub struct ProposalFilter {
+    status: Option<ProposalStatus>,
+    kind: Option<ProposalKind>,
+    author: Option<ValidAccountId>,
+    created_from: Option<U64>,
+    created_to: Option<U64>,
+}
+
+impl ProposalFilter {
+    fn is_match(&self, proposal: &ProposalState) -> bool {
+        self.status.iter().all(|status| *status == proposal.status)
+            && self.kind.iter().all(|kind| *kind == proposal.kind)
+            && self
+                .author
+                .iter()
+                .all(|author| author.as_ref() == &proposal.author)
+            && self
+                .created_from
+                .iter()
+                .all(|from| from.0 <= proposal.timestamp)
+            && self.created_to.iter().all(|to| proposal.timestamp <= to.0)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ProposalOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalPage {
+    total: Option<u64>,
+    proposals: Vec<Proposal>,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize)]
+#[serde(crate = "near_sdk::serde")]
+pub struct ProposalState {
+    timestamp: u64,
+    title: String,
+    kind: ProposalKind,
+    status: ProposalStatus,
+    description: String,
+    script: Option<String>,
+    author: AccountId,
+    vote: ProposalVote,
+    bond: Balance,
+    archived: bool,
+    attachments: Vec<Attachment>,
+}
+
+impl ProposalState {
+    pub fn new(
+        title: Option<String>,
+        description: Option<String>,
+        author: AccountId,
+        kind: ProposalKind,
+        status: ProposalStatus,
+        script: Option<String>,
+    ) -> Self {
+        let title = title.unwrap_or_default();
+        let description = description.unwrap_or_default();
+        assert_proposal_text(&title, &description);
+        Self {
+            timestamp: env::block_timestamp(),
+            title,
+            kind,
+            status,
+            description,
+            script,
+            author,
+            vote: ProposalVote {
+                approve: 0,
+                reject: 0,
+                spam: 0,
+            },
+            bond: 0,
+            archived: false,
+            attachments: vec![],
+        }
+    }
+
+    fn into_proposal(self, id: u64) -> Proposal {
+        Proposal {
+            id,
+            timestamp: self.timestamp,
+            title: self.title,
+            kind: self.kind,
+            status: self.status,
+            description: self.description,
+            script: self.script,
+            author: self.author,
+            vote: self.vote,
+            bond: U128(self.bond),
+            archived: self.archived,
+            attachments: self.attachments,
+        }
+    }
+
+    fn is_draft(&self) -> bool {
+        self.status == ProposalStatus::Draft
+    }
+
+    fn is_accepted(&self) -> bool {
+        self.status == ProposalStatus::Accepted
+    }
+
+    fn is_rejected(&self) -> bool {
+        self.status == ProposalStatus::Rejected
+    }
+
+    fn is_finalized(&self) -> bool {
+        self.is_accepted() || self.is_rejected()
+    }
+
+    /// Drop the body of a finalized proposal and keep only the outcome
+    fn archive(&mut self) {
+        self.description = String::new();
+        self.script = None;
+        self.attachments = vec![];
+        self.archived = true;
+    }
+
+    fn script<T: DeserializeOwned>(&self) -> T {
+        let script = self.script.clone();
+        parse_script(&script.unwrap_or_else(|| "{}".to_string()))
+    }
+
+    /// Amount reserved from the fund while the proposal is in vote
+    fn fund(&self) -> Balance {
+        match self.kind {
+            ProposalKind::FundRequest => u128::from(self.script::<FundScript>().fund),
+            ProposalKind::FunctionCall => self.script::<FunctionCallScript>().deposit(),
+            ProposalKind::StakeRequest => u128::from(self.script::<StakeScript>().amount),
+            ProposalKind::StreamRequest => u128::from(self.script::<StreamScript>().total),
+            ProposalKind::MilestoneGrant => self.script::<MilestoneGrantScript>().total(),
+            ProposalKind::Bounty => u128::from(self.script::<BountyScript>().reward),
+            ProposalKind::AcquireNft => u128::from(self.script::<AcquireNftScript>().price),
+            ProposalKind::MemberRequest
+            | ProposalKind::UnstakeRequest
+            | ProposalKind::WithdrawRequest
+            | ProposalKind::StreamCancel
+            | ProposalKind::GrantCancel
+            | ProposalKind::BountyDone
+            | ProposalKind::BountyCancel
+            | ProposalKind::PolicyUpdate
+            | ProposalKind::MemberRemove
+            | ProposalKind::ArtMint
+            | ProposalKind::AuctionArtwork
+            | ProposalKind::Exhibition
+            | ProposalKind::CommentHide => 0,
+        }
+    }
+
+    /// Amount released from the reserve when the proposal is accepted
+    fn release(&self) -> Balance {
+        match self.kind {
+            // the stream, the grant and the bounty keep the total reserved until paid or canceled
+            ProposalKind::StreamRequest | ProposalKind::MilestoneGrant | ProposalKind::Bounty => 0,
+            _ => self.fund(),
+        }
+    }
+
+    fn vote(&mut self, resolve: bool, spam: bool, max: u64) {
+        if resolve {
+            self.vote.approve += 1;
+        } else {
+            self.vote.reject += 1;
+        }
+        if spam {
+            self.vote.spam += 1;
+        }
+        self.calc(max);
+    }
+
+    fn calc(&mut self, total: u64) {
+        if !self.consensus(total) {
+            return;
+        }
+        if self.vote.is_approve() {
+            self.status = ProposalStatus::Accepted;
+        } else if self.vote.is_reject() {
+            self.status = ProposalStatus::Rejected;
+        } else if self.quorum() >= total {
+            self.status = ProposalStatus::Draft;
+            self.vote.reject = 0;
+            self.vote.approve = 0;
+            self.vote.spam = 0;
+        }
+    }
+
+    fn consensus(&self, max: u64) -> bool {
+        consensus(max, self.quorum())
+    }
+
+    fn quorum(&self) -> u64 {
+        self.vote.approve + self.vote.reject
+    }
+}
+
+#[derive(BorshSerialize, BorshStorageKey)]
+enum StorageKey {
+    MemberList,
+    ProposalList,
+    ProposalVote { hash: CryptoHash },
+    VoteList,
+    ActiveProposal,
+    StakeList,
+    StreamList,
+    GrantList,
+    MilestoneVote { hash: CryptoHash },
+    MilestoneVoteList,
+    BountyList,
+    TokenList,
+    TokenOwner { hash: CryptoHash },
+    TokenOwnerList,
+    AcquisitionList,
+    AcquisitionIndex,
+    AuctionList,
+    AuctionIndex,
+    ExhibitionList,
+    StorageList,
+    AuthorProposal { hash: CryptoHash },
+    AuthorProposalList,
+    AuthorActive { hash: CryptoHash },
+    ProposalRevision { hash: CryptoHash },
+    RevisionList,
+    ProposalComment { hash: CryptoHash },
+    CommentList,
+    ProfileList,
+    DuesList,
+}
+
+#[near_bindgen]
+#[derive(PanicOnDefault, BorshDeserialize, BorshSerialize)]
+pub struct Society {
+    member_list: UnorderedSet<AccountId>,
+    proposal_list: Vector<ProposalState>,
+    vote_list: LookupMap<u64, UnorderedSet<AccountId>>,
+    active_proposal: LookupMap<AccountId, UnorderedSet<u64>>,
+    fund_proposal: Balance,
+    stake_list: UnorderedMap<AccountId, StakeState>,
+    stream_list: Vector<StreamState>,
+    grant_list: Vector<GrantState>,
+    milestone_vote_list: LookupMap<(u64, u64), UnorderedSet<AccountId>>,
+    bounty_list: Vector<BountyState>,
+    policy: Policy,
+    token_list: UnorderedMap<String, TokenState>,
+    token_owner_list: LookupMap<AccountId, UnorderedSet<String>>,
+    art_total: u64,
+    /// Royalties the payouts asked markets to send, the NEAR itself arrives as a plain transfer
+    royalty_total: Balance,
+    acquisition_list: Vector<Acquisition>,
+    acquisition_index: LookupMap<String, u64>,
+    auction_list: Vector<Auction>,
+    auction_index: LookupMap<String, u64>,
+    exhibition_list: Vector<ExhibitionState>,
+    storage_list: LookupMap<AccountId, StorageState>,
+    storage_total: Balance,
+    storage_used: StorageUsage,
+    author_proposal_list: LookupMap<AccountId, Vector<u64>>,
+    revision_list: LookupMap<u64, Vector<ProposalRevision>>,
+    comment_list: LookupMap<u64, Vector<CommentState>>,
+    profile_list: LookupMap<AccountId, MemberProfileState>,
+    dues_list: LookupMap<AccountId, u64>,
+    dues_start: u64,
+}
+
+#[near_bindgen]
+impl Society {
+    #[init]
+    pub fn init(initial_members: Vec<ValidAccountId>) -> Self {
+        require(!env::state_exists(), SocietyError::AlreadyInitialized);
+        require(
+            !initial_members.is_empty(),
+            SocietyError::InitialMemberRequired,
+        );
+        let mut contract = Self::new();
+        contract.setup(initial_members);
+        contract
+    }
+
+    fn new() -> Self {
+        Self {
+            member_list: UnorderedSet::new(StorageKey::MemberList),
+            proposal_list: Vector::new(StorageKey::ProposalList),
+            vote_list: LookupMap::new(StorageKey::VoteList),
+            active_proposal: LookupMap::new(StorageKey::ActiveProposal),
+            fund_proposal: 0,
+            stake_list: UnorderedMap::new(StorageKey::StakeList),
+            stream_list: Vector::new(StorageKey::StreamList),
+            grant_list: Vector::new(StorageKey::GrantList),
+            milestone_vote_list: LookupMap::new(StorageKey::MilestoneVoteList),
+            bounty_list: Vector::new(StorageKey::BountyList),
+            policy: Policy::default(),
+            token_list: UnorderedMap::new(StorageKey::TokenList),
+            token_owner_list: LookupMap::new(StorageKey::TokenOwnerList),
+            art_total: 0,
+            royalty_total: 0,
+            acquisition_list: Vector::new(StorageKey::AcquisitionList),
+            acquisition_index: LookupMap::new(StorageKey::AcquisitionIndex),
+            auction_list: Vector::new(StorageKey::AuctionList),
+            auction_index: LookupMap::new(StorageKey::AuctionIndex),
+            exhibition_list: Vector::new(StorageKey::ExhibitionList),
+            storage_list: LookupMap::new(StorageKey::StorageList),
+            storage_total: 0,
+            storage_used: 0,
+            author_proposal_list: LookupMap::new(StorageKey::AuthorProposalList),
+            revision_list: LookupMap::new(StorageKey::RevisionList),
+            comment_list: LookupMap::new(StorageKey::CommentList),
+            profile_list: LookupMap::new(StorageKey::ProfileList),
+            dues_list: LookupMap::new(StorageKey::DuesList),
+            dues_start: 0,
+        }
+    }
+
+    /// Initial members are registered without a deposit, the deployment pays for their records
+    fn setup(&mut self, initial_members: Vec<ValidAccountId>) {
+        for member in initial_members {
+            let account_id: AccountId = member.into();
+            self.storage_list
+                .insert(&account_id, &StorageState::default());
+            self.add_member(account_id);
+        }
+    }
+
+    fn account_locked_for_storage(&self) -> u128 {
+        storage_cost(env::storage_usage())
+    }
+
+    fn fund(&self) -> Balance {
+        env::account_balance()
+            - env::account_locked_balance()
+            - self.account_locked_for_storage()
+            - PARAM_FUND_RESERVE
+            - self.fund_proposal
+            - self.storage_available()
+    }
+
+    /// Storage deposits of the accounts which are not spent on storage yet
+    fn storage_available(&self) -> Balance {
+        self.storage_total - storage_cost(self.storage_used)
+    }
+
+    /// Charge the storage growth since `initial_storage` to the account or give back the released one
+    fn charge_storage(&mut self, account_id: &AccountId, initial_storage: StorageUsage) {
+        let mut storage = match self.storage_list.get(account_id) {
+            Some(storage) => storage,
+            None => SocietyError::NotRegistered(account_id.clone()).panic(),
+        };
+        let current_storage = env::storage_usage();
+        if current_storage >= initial_storage {
+            let used = current_storage - initial_storage;
+            storage.used += used;
+            self.storage_used += used;
+            let required = storage_cost(storage.used);
+            if required > storage.total {
+                SocietyError::StorageDeposit(required - storage.total).panic()
+            }
+        } else {
+            let released = std::cmp::min(initial_storage - current_storage, storage.used);
+            storage.used -= released;
+            self.storage_used -= released;
+        }
+        self.storage_list.insert(account_id, &storage);
+    }
+
+    /// Give the storage released since `initial_storage` back to the account which paid for it
+    fn release_storage(&mut self, account_id: &AccountId, initial_storage: StorageUsage) {
+        let mut storage = match self.storage_list.get(account_id) {
+            Some(storage) => storage,
+            None => return,
+        };
+        let released = std::cmp::min(
+            initial_storage.saturating_sub(env::storage_usage()),
+            storage.used,
+        );
+        storage.used -= released;
+        self.storage_used -= released;
+        self.storage_list.insert(account_id, &storage);
+    }
+
+    fn assert_storage_available(&self, account_id: &AccountId, storage_used: StorageUsage) {
+        if let Some(error) = self.storage_error(account_id, storage_used) {
+            error.panic()
+        }
+    }
+
+    fn storage_error(
+        &self,
+        account_id: &AccountId,
+        storage_used: StorageUsage,
+    ) -> Option<SocietyError> {
+        let available = self
+            .storage_list
+            .get(account_id)
+            .map(|storage| storage.available())
+            .unwrap_or_default();
+        let required = storage_cost(storage_used);
+        if required > available {
+            return Some(SocietyError::StorageDeposit(required - available));
+        }
+        None
+    }
+
+    fn storage_balance(&self, account_id: &AccountId) -> Option<StorageBalance> {
+        self.storage_list
+            .get(account_id)
+            .map(|storage| StorageBalance {
+                total: U128(storage.total),
+                available: U128(storage.available()),
+            })
+    }
+
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<ValidAccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let account_id: AccountId = match account_id {
+            Some(account_id) => account_id.into(),
+            None => env::predecessor_account_id(),
+        };
+        let mut amount = env::attached_deposit();
+        let storage = match self.storage_list.get(&account_id) {
+            Some(storage) => {
+                if registration_only.unwrap_or(false) {
+                    Promise::new(env::predecessor_account_id()).transfer(amount);
+                    amount = 0;
+                }
+                storage
+            }
+            None => {
+                let min = self.storage_balance_bounds().min.0;
+                require(amount >= min, SocietyError::RegistrationDeposit(min));
+                if registration_only.unwrap_or(false) {
+                    Promise::new(env::predecessor_account_id()).transfer(amount - min);
+                    amount = min;
+                }
+                let initial_storage = env::storage_usage();
+                self.storage_list
+                    .insert(&account_id, &StorageState::default());
+                let used = env::storage_usage() - initial_storage;
+                self.storage_used += used;
+                StorageState { total: 0, used }
+            }
+        };
+        let storage = StorageState {
+            total: storage.total + amount,
+            used: storage.used,
+        };
+        self.storage_total += amount;
+        self.storage_list.insert(&account_id, &storage);
+        self.storage_balance(&account_id).unwrap()
+    }
+
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        require(env::attached_deposit() == 1, SocietyError::OneYoctoRequired);
+        let account_id = env::predecessor_account_id();
+        let mut storage = match self.storage_list.get(&account_id) {
+            Some(storage) => storage,
+            None => SocietyError::NotRegistered(account_id).panic(),
+        };
+        let available = storage.available();
+        let amount = amount.map(|amount| amount.0).unwrap_or(available);
+        require(
+            amount <= available,
+            SocietyError::StorageWithdraw(available),
+        );
+        storage.total -= amount;
+        self.storage_total -= amount;
+        self.storage_list.insert(&account_id, &storage);
+        if amount > 0 {
+            Promise::new(account_id.clone()).transfer(amount);
+        }
+        self.storage_balance(&account_id).unwrap()
+    }
+
+    pub fn storage_balance_of(&self, account_id: ValidAccountId) -> Option<StorageBalance> {
+        self.storage_balance(account_id.as_ref())
+    }
+
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        StorageBalanceBounds {
+            min: U128(storage_cost(PARAM_STORAGE_ACCOUNT)),
+            max: None,
+        }
+    }
+
+    fn stake(&self, pool_id: &AccountId) -> StakeState {
+        self.stake_list.get(pool_id).unwrap_or_default()
+    }
+
+    pub fn balance(&self) -> FundBalance {
+        let mut staked: Balance = 0;
+        let mut unstaking: Balance = 0;
+        for stake in self.stake_list.values() {
+            staked += stake.staked;
+            unstaking += stake.unstaking;
+        }
+        FundBalance {
+            liquid: U128(self.fund()),
+            staked: U128(staked),
+            unstaking: U128(unstaking),
+            royalty: U128(self.royalty_total),
+        }
+    }
+
+    pub fn stake_list(&self) -> Vec<Stake> {
+        self.stake_list
+            .iter()
+            .map(|(pool_id, state)| Stake {
+                pool_id,
+                staked: U128(state.staked),
+                unstaking: U128(state.unstaking),
+            })
+            .collect()
+    }
+
+    /// Stake is counted on success, unstake and withdraw are taken on execution and given back on failure
+    #[private]
+    pub fn on_staking_pool(&mut self, pool_id: AccountId, kind: ProposalKind, amount: U128) {
+        let success = is_promise_success();
+        let mut stake = self.stake(&pool_id);
+        match kind {
+            ProposalKind::StakeRequest if success => stake.staked += amount.0,
+            ProposalKind::UnstakeRequest if !success => {
+                stake.staked += amount.0;
+                stake.unstaking = stake.unstaking.saturating_sub(amount.0);
+            }
+            ProposalKind::WithdrawRequest if !success => stake.unstaking += amount.0,
+            ProposalKind::StakeRequest
+            | ProposalKind::UnstakeRequest
+            | ProposalKind::WithdrawRequest => return,
+            _ => SocietyError::UnexpectedKind.panic(),
+        }
+        self.set_stake(&pool_id, &stake);
+    }
+
+    fn set_stake(&mut self, pool_id: &AccountId, stake: &StakeState) {
+        if stake.staked == 0 && stake.unstaking == 0 {
+            self.stake_list.remove(pool_id);
+        } else {
+            self.stake_list.insert(pool_id, stake);
+        }
+    }
+
+    #[private]
+    pub fn on_acquire_nft(&mut self, acquisition_id: u64) {
+        let mut acquisition = match self.acquisition_list.get(acquisition_id) {
+            Some(acquisition) => acquisition,
+            None => SocietyError::AcquisitionNotFound.panic(),
+        };
+        // the attached price is refunded by the protocol when the market call fails
+        if !is_promise_success() && acquisition.status == AcquisitionStatus::Pending {
+            acquisition.status = AcquisitionStatus::Failed;
+            self.acquisition_index.remove(&nft_key(
+                &acquisition.nft_contract_id,
+                &acquisition.token_id,
+            ));
+            self.acquisition_list.replace(acquisition_id, &acquisition);
+        }
+    }
+
+    /// Fail a purchase whose token never arrived, a later delivery is returned to the sender and
+    /// the token can be proposed again. The price left the treasury with the market call
+    pub fn expire_acquisition(&mut self, acquisition_id: u64) {
+        let mut acquisition = match self.acquisition_list.get(acquisition_id) {
+            Some(acquisition) => acquisition,
+            None => SocietyError::AcquisitionNotFound.panic(),
+        };
+        let deadline = acquisition
+            .timestamp
+            .0
+            .saturating_add(PARAM_ACQUISITION_TIMEOUT);
+        if acquisition.status != AcquisitionStatus::Pending || env::block_timestamp() < deadline {
+            SocietyError::AcquisitionPending.panic()
+        }
+        acquisition.status = AcquisitionStatus::Failed;
+        self.acquisition_index.remove(&nft_key(
+            &acquisition.nft_contract_id,
+            &acquisition.token_id,
+        ));
+        self.acquisition_list.replace(acquisition_id, &acquisition);
+    }
+
+    fn auction(&self, auction_id: u64) -> Auction {
+        match self.auction_list.get(auction_id) {
+            Some(auction) => auction,
+            None => SocietyError::AuctionNotFound.panic(),
+        }
+    }
+
+    #[payable]
+    pub fn bid(&mut self, auction_id: u64) {
+        let mut auction = self.auction(auction_id);
+        if auction.settled || auction.end.0 <= env::block_timestamp() {
+            SocietyError::AuctionClosed.panic()
+        }
+        let amount = env::attached_deposit();
+        let min_amount = match auction.bid.as_ref() {
+            Some(bid) => bid.amount.0 + auction.min_increment.0,
+            None => auction.reserve_price.0,
+        };
+        require(amount >= min_amount, SocietyError::BidTooLow(min_amount));
+        if let Some(bid) = auction.bid.take() {
+            self.fund_proposal -= bid.amount.0;
+            self.payout(bid.account_id, bid.amount.0, "auction_refund");
+        }
+        self.fund_proposal += amount;
+        auction.bid = Some(Bid {
+            account_id: env::predecessor_account_id(),
+            amount: U128(amount),
+        });
+        self.auction_list.replace(auction_id, &auction);
+    }
+
+    pub fn settle_auction(&mut self, auction_id: u64) {
+        let mut auction = self.auction(auction_id);
+        if auction.settled {
+            SocietyError::AuctionSettled.panic()
+        }
+        if auction.end.0 > env::block_timestamp() {
+            SocietyError::AuctionNotFinished.panic()
+        }
+        auction.settled = true;
+        self.auction_index
+            .remove(&nft_key(&auction.nft_contract_id, &auction.token_id));
+        self.auction_list.replace(auction_id, &auction);
+        let bid = match auction.bid {
+            Some(bid) => bid,
+            None => return,
+        };
+        if auction.nft_contract_id != env::current_account_id() {
+            ext_nft::nft_transfer(
+                bid.account_id,
+                auction.token_id,
+                None,
+                None,
+                &auction.nft_contract_id,
+                1,
+                PARAM_GAS_NFT_TRANSFER,
+            )
+            .then(ext_self::on_settle_auction(
+                auction_id,
+                &env::current_account_id(),
+                0,
+                PARAM_GAS_CALLBACK,
+            ));
+            return;
+        }
+        self.fund_proposal -= bid.amount.0;
+        match self.token_list.get(&auction.token_id) {
+            Some(token) if token.owner_id == env::current_account_id() => {
+                self.transfer_token(&token.owner_id, &bid.account_id, &auction.token_id, None);
+            }
+            _ => {
+                self.payout(bid.account_id, bid.amount.0, "auction_refund");
+            }
+        }
+    }
+
+    #[private]
+    pub fn on_settle_auction(&mut self, auction_id: u64) {
+        let auction = self.auction(auction_id);
+        if let Some(bid) = auction.bid {
+            self.fund_proposal -= bid.amount.0;
+            if !is_promise_success() {
+                self.payout(bid.account_id, bid.amount.0, "auction_refund");
+            }
+        }
+    }
+
+    fn exhibition_state(&self, exhibition_id: u64) -> ExhibitionState {
+        match self.exhibition_list.get(exhibition_id) {
+            Some(exhibition) => exhibition,
+            None => SocietyError::ExhibitionNotFound.panic(),
+        }
+    }
+
+    /// Curator reorders the items, the order lists every current item index once
+    pub fn reorder_exhibition(&mut self, exhibition_id: u64, order: Vec<u64>) {
+        let mut exhibition = self.exhibition_state(exhibition_id);
+        require(
+            exhibition.curator == env::predecessor_account_id(),
+            SocietyError::OnlyCurator,
+        );
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        require(
+            sorted.len() == exhibition.items.len()
+                && sorted
+                    .iter()
+                    .enumerate()
+                    .all(|(index, item)| *item == index as u64),
+            SocietyError::ExhibitionOrder,
+        );
+        exhibition.items = order
+            .iter()
+            .map(|index| exhibition.items[*index as usize].clone())
+            .collect();
+        self.exhibition_list.replace(exhibition_id, &exhibition);
+    }
+
+    /// NEP-171 receiver, keeps only the tokens acquired by proposal
+    pub fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: String,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        let _ = (sender_id, previous_owner_id, msg);
+        let key = nft_key(&env::predecessor_account_id(), &token_id);
+        let acquisition_id = match self.acquisition_index.get(&key) {
+            Some(acquisition_id) => acquisition_id,
+            None => return PromiseOrValue::Value(true),
+        };
+        let mut acquisition = match self.acquisition_list.get(acquisition_id) {
+            Some(acquisition) => acquisition,
+            None => SocietyError::AcquisitionNotFound.panic(),
+        };
+        acquisition.status = AcquisitionStatus::Acquired;
+        self.acquisition_index.remove(&key);
+        self.acquisition_list.replace(acquisition_id, &acquisition);
+        PromiseOrValue::Value(false)
+    }
+
+    pub fn is_member(&self, account_id: AccountId) -> bool {
+        self.member_list.contains(&account_id)
+    }
+
+    fn add_member(&mut self, account_id: AccountId) -> bool {
+        self.assert_is_member(account_id.clone());
+        self.mint_token(
+            member_token_id(&account_id),
+            account_id.clone(),
+            TokenMetadata {
+                title: Some(format!("{} member", PARAM_NFT_NAME)),
+                issued_at: Some((env::block_timestamp() / 1_000_000).to_string()),
+                copies: Some(1),
+                ..TokenMetadata::default()
+            },
+            HashMap::new(),
+        );
+        log_event("member_added", json!({ "account_id": account_id }));
+        self.member_list.insert(&account_id)
+    }
+
+    /// The storage of the profile and the dues goes back to the member
+    fn remove_member(&mut self, account_id: AccountId) -> bool {
+        self.burn_token(member_token_id(&account_id));
+        let initial_storage = env::storage_usage();
+        self.profile_list.remove(&account_id);
+        self.dues_list.remove(&account_id);
+        self.release_storage(&account_id, initial_storage);
+        log_event("member_removed", json!({ "account_id": account_id }));
+        self.member_list.remove(&account_id)
+    }
+
+    /// Profile of the member, empty until the first edit. The join time comes from the member
+    /// token and the admission from the last accepted member request
+    fn member_profile(&self, account_id: AccountId) -> MemberProfile {
+        let profile = self.profile_list.get(&account_id).unwrap_or_default();
+        let admitted_by_proposal = self
+            .author_proposal_list(&account_id)
+            .iter()
+            .filter(|proposal_id| match self.proposal_list.get(*proposal_id) {
+                Some(proposal) => {
+                    proposal.kind == ProposalKind::MemberRequest
+                        && proposal.status == ProposalStatus::Accepted
+                }
+                None => false,
+            })
+            .last();
+        MemberProfile {
+            joined_at: U64(self.joined_at(&account_id)),
+            account_id,
+            name: profile.name,
+            avatar: profile.avatar,
+            bio: profile.bio,
+            links: profile.links,
+            admitted_by_proposal,
+        }
+    }
+
+    /// Issue time of the member token, in nanoseconds
+    fn joined_at(&self, account_id: &AccountId) -> u64 {
+        self.token_list
+            .get(&member_token_id(account_id))
+            .and_then(|token| token.metadata.issued_at)
+            .and_then(|issued_at| issued_at.parse::<u64>().ok())
+            .unwrap_or_default()
+            * 1_000_000
+    }
+
+    fn author_proposal_list(&self, account_id: &AccountId) -> Vector<u64> {
+        match self.author_proposal_list.get(account_id) {
+            Some(proposal_list) => proposal_list,
+            None => Vector::new(StorageKey::AuthorProposal {
+                hash: hash(account_id.clone()),
+            }),
+        }
+    }
+
+    fn token_owner_list(&self, account_id: &AccountId) -> UnorderedSet<String> {
+        match self.token_owner_list.get(account_id) {
+            Some(token_list) => token_list,
+            None => UnorderedSet::new(StorageKey::TokenOwner {
+                hash: hash(account_id.clone()),
+            }),
+        }
+    }
+
+    fn insert_token_owner(&mut self, owner_id: &AccountId, token_id: &str) {
+        let mut token_list = self.token_owner_list(owner_id);
+        token_list.insert(&token_id.to_string());
+        self.token_owner_list.insert(owner_id, &token_list);
+    }
+
+    fn remove_token_owner(&mut self, owner_id: &AccountId, token_id: &str) {
+        let mut token_list = self.token_owner_list(owner_id);
+        token_list.remove(&token_id.to_string());
+        if token_list.is_empty() {
+            self.token_owner_list.remove(owner_id);
+        } else {
+            self.token_owner_list.insert(owner_id, &token_list);
+        }
+    }
+
+    fn mint_token(
+        &mut self,
+        token_id: String,
+        owner_id: AccountId,
+        metadata: TokenMetadata,
+        royalty: HashMap<AccountId, u32>,
+    ) {
+        if self.token_list.get(&token_id).is_some() {
+            SocietyError::TokenExists(token_id).panic()
+        }
+        self.insert_token_owner(&owner_id, &token_id);
+        self.token_list.insert(
+            &token_id,
+            &TokenState {
+                owner_id,
+                metadata,
+                royalty,
+                approved_account_ids: HashMap::new(),
+                next_approval_id: 0,
+            },
+        );
+    }
+
+    fn burn_token(&mut self, token_id: String) {
+        if let Some(token) = self.token_list.remove(&token_id) {
+            self.remove_token_owner(&token.owner_id, &token_id);
+        }
+    }
+
+    /// Move the token by the owner or an approved account, the approvals are cleared and
+    /// returned with the previous owner
+    fn transfer_token(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &str,
+        approval_id: Option<u64>,
+    ) -> (AccountId, HashMap<AccountId, u64>) {
+        let mut token = self.token_state(token_id);
+        if is_member_token(token_id) {
+            SocietyError::TokenNotTransferable.panic()
+        }
+        if &token.owner_id != sender_id {
+            match token.approved_account_ids.get(sender_id) {
+                Some(id) if approval_id.unwrap_or(*id) == *id => {}
+                Some(_) => SocietyError::NotApproved.panic(),
+                None => SocietyError::OnlyTokenOwner.panic(),
+            }
+        }
+        require(&token.owner_id != receiver_id, SocietyError::TokenSameOwner);
+        let previous_owner_id = token.owner_id;
+        let approved_account_ids = std::mem::take(&mut token.approved_account_ids);
+        self.remove_token_owner(&previous_owner_id, token_id);
+        self.insert_token_owner(receiver_id, token_id);
+        token.owner_id = receiver_id.clone();
+        self.token_list.insert(&token_id.to_string(), &token);
+        (previous_owner_id, approved_account_ids)
+    }
+
+    fn token_state(&self, token_id: &str) -> TokenState {
+        match self.token_list.get(&token_id.to_string()) {
+            Some(token) => token,
+            None => SocietyError::TokenNotFound.panic(),
+        }
+    }
+
+    /// The owner gets back the storage deposit of the cleared approvals
+    fn refund_approvals(
+        &self,
+        owner_id: &AccountId,
+        approved_account_ids: &HashMap<AccountId, u64>,
+    ) {
+        let storage: StorageUsage = approved_account_ids
+            .keys()
+            .map(|account_id| approval_storage(account_id))
+            .sum();
+        if storage > 0 && owner_id != &env::current_account_id() {
+            Promise::new(owner_id.clone()).transfer(storage_cost(storage));
+        }
+    }
+
+    fn mint_art(&mut self, script: ArtMintScript) {
+        let owner_id: AccountId = match script.receiver_id {
+            Some(receiver_id) => receiver_id.into(),
+            None => env::current_account_id(),
+        };
+        let copies = script.copies.unwrap_or(1);
+        let royalty = script.royalty.unwrap_or_default();
+        for _ in 0..copies {
+            let token_id = self.art_total.to_string();
+            self.art_total += 1;
+            self.mint_token(
+                token_id,
+                owner_id.clone(),
+                TokenMetadata {
+                    title: Some(script.title.clone()),
+                    description: script.description.clone(),
+                    media: Some(script.media.clone()),
+                    media_hash: Some(script.media_hash.clone()),
+                    copies: Some(copies),
+                    issued_at: Some((env::block_timestamp() / 1_000_000).to_string()),
+                    ..TokenMetadata::default()
+                },
+                royalty.clone(),
+            );
+        }
+    }
+
+    fn token(&self, token_id: String) -> Option<Token> {
+        self.token_list.get(&token_id).map(|state| Token {
+            token_id,
+            owner_id: state.owner_id,
+            metadata: Some(state.metadata),
+            approved_account_ids: state.approved_account_ids,
+        })
+    }
+
+    #[payable]
+    pub fn nft_transfer(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        require(env::attached_deposit() == 1, SocietyError::OneYoctoRequired);
+        let _ = memo;
+        let (previous_owner_id, approved_account_ids) = self.transfer_token(
+            &env::predecessor_account_id(),
+            receiver_id.as_ref(),
+            &token_id,
+            approval_id,
+        );
+        self.refund_approvals(&previous_owner_id, &approved_account_ids);
+    }
+
+    #[payable]
+    pub fn nft_transfer_call(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        require(env::attached_deposit() == 1, SocietyError::OneYoctoRequired);
+        let _ = memo;
+        let sender_id = env::predecessor_account_id();
+        let receiver_id: AccountId = receiver_id.into();
+        let (previous_owner_id, approved_account_ids) =
+            self.transfer_token(&sender_id, &receiver_id, &token_id, approval_id);
+        ext_nft_receiver::nft_on_transfer(
+            sender_id,
+            previous_owner_id.clone(),
+            token_id.clone(),
+            msg,
+            &receiver_id,
+            0,
+            PARAM_GAS_NFT_ON_TRANSFER,
+        )
+        .then(ext_self::nft_resolve_transfer(
+            previous_owner_id,
+            receiver_id,
+            token_id,
+            approved_account_ids,
+            &env::current_account_id(),
+            0,
+            PARAM_GAS_CALLBACK,
+        ))
+        .into()
+    }
+
+    /// Returns the token with its approvals when the receiver asks for it, otherwise the
+    /// previous owner gets back the storage of the approvals
+    #[private]
+    pub fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: String,
+        approved_account_ids: HashMap<AccountId, u64>,
+    ) -> bool {
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::Successful(value) => {
+                serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            _ => true,
+        };
+        if must_revert {
+            if let Some(token) = self.token_list.get(&token_id) {
+                if token.owner_id == receiver_id {
+                    let (_, receiver_approvals) =
+                        self.transfer_token(&receiver_id, &previous_owner_id, &token_id, None);
+                    self.refund_approvals(&receiver_id, &receiver_approvals);
+                    let mut token = self.token_state(&token_id);
+                    token.approved_account_ids = approved_account_ids;
+                    self.token_list.insert(&token_id, &token);
+                    return false;
+                }
+            }
+        }
+        self.refund_approvals(&previous_owner_id, &approved_account_ids);
+        true
+    }
+
+    /// NEP-178 approval, the attached deposit pays the storage of the approval and the
+    /// receiver is notified when the message is set
+    #[payable]
+    pub fn nft_approve(
+        &mut self,
+        token_id: String,
+        account_id: ValidAccountId,
+        msg: Option<String>,
+    ) -> Option<Promise> {
+        let mut token = self.token_state(&token_id);
+        if is_member_token(&token_id) {
+            SocietyError::TokenNotTransferable.panic()
+        }
+        require(
+            token.owner_id == env::predecessor_account_id(),
+            SocietyError::OnlyTokenOwner,
+        );
+        let account_id: AccountId = account_id.into();
+        let approval_id = token.next_approval_id;
+        let storage = match token
+            .approved_account_ids
+            .insert(account_id.clone(), approval_id)
+        {
+            Some(_) => 0,
+            None => approval_storage(&account_id),
+        };
+        let required = std::cmp::max(storage_cost(storage), 1);
+        let deposit = env::attached_deposit();
+        if deposit < required {
+            SocietyError::StorageDeposit(required - deposit).panic()
+        }
+        if deposit > required {
+            Promise::new(env::predecessor_account_id()).transfer(deposit - required);
+        }
+        token.next_approval_id += 1;
+        self.token_list.insert(&token_id, &token);
+        msg.map(|msg| {
+            ext_nft_approval_receiver::nft_on_approve(
+                token_id,
+                token.owner_id,
+                approval_id,
+                msg,
+                &account_id,
+                0,
+                PARAM_GAS_NFT_ON_APPROVE,
+            )
+        })
+    }
+
+    #[payable]
+    pub fn nft_revoke(&mut self, token_id: String, account_id: ValidAccountId) {
+        require(env::attached_deposit() == 1, SocietyError::OneYoctoRequired);
+        let mut token = self.token_state(&token_id);
+        require(
+            token.owner_id == env::predecessor_account_id(),
+            SocietyError::OnlyTokenOwner,
+        );
+        let account_id: AccountId = account_id.into();
+        if let Some(approval_id) = token.approved_account_ids.remove(&account_id) {
+            self.token_list.insert(&token_id, &token);
+            let revoked = [(account_id, approval_id)].iter().cloned().collect();
+            self.refund_approvals(&token.owner_id, &revoked);
+        }
+    }
+
+    #[payable]
+    pub fn nft_revoke_all(&mut self, token_id: String) {
+        require(env::attached_deposit() == 1, SocietyError::OneYoctoRequired);
+        let mut token = self.token_state(&token_id);
+        require(
+            token.owner_id == env::predecessor_account_id(),
+            SocietyError::OnlyTokenOwner,
+        );
+        let revoked = std::mem::take(&mut token.approved_account_ids);
+        self.token_list.insert(&token_id, &token);
+        self.refund_approvals(&token.owner_id, &revoked);
+    }
+
+    pub fn nft_is_approved(
+        &self,
+        token_id: String,
+        approved_account_id: ValidAccountId,
+        approval_id: Option<u64>,
+    ) -> bool {
+        let token = self.token_state(&token_id);
+        match token.approved_account_ids.get(approved_account_id.as_ref()) {
+            Some(id) => approval_id.unwrap_or(*id) == *id,
+            None => false,
+        }
+    }
+
+    pub fn nft_payout(&self, token_id: String, balance: U128, max_len_payout: u32) -> Payout {
+        let token = self.token_state(&token_id);
+        let payout = token.payout(balance.0);
+        require(
+            payout.len() <= max_len_payout as usize,
+            SocietyError::PayoutTooLong,
+        );
+        Payout { payout }
+    }
+
+    /// NEP-199 transfer by the owner or an approved market. The payout is only what the market
+    /// is asked to pay, the society royalty is counted when it arrives with pay_royalty
+    #[payable]
+    pub fn nft_transfer_payout(
+        &mut self,
+        receiver_id: ValidAccountId,
+        token_id: String,
+        approval_id: Option<u64>,
+        balance: U128,
+        max_len_payout: u32,
+    ) -> Payout {
+        let payout = self.nft_payout(token_id.clone(), balance, max_len_payout);
+        self.nft_transfer(receiver_id, token_id, approval_id, None);
+        payout
+    }
+
+    /// Market pays the society royalty of the token sale, the attached deposit is counted
+    #[payable]
+    pub fn pay_royalty(&mut self, token_id: String) {
+        let amount = env::attached_deposit();
+        require(amount != 0, SocietyError::ZeroValue("royalty"));
+        self.token_state(&token_id);
+        self.royalty_total += amount;
+        log_event(
+            "royalty_received",
+            json!({
+                "token_id": token_id,
+                "account_id": env::predecessor_account_id(),
+                "amount": U128(amount),
+            }),
+        );
+    }
+
+    pub fn nft_token(&self, token_id: String) -> Option<Token> {
+        self.token(token_id)
+    }
+
+    pub fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: PARAM_NFT_SPEC.to_string(),
+            name: PARAM_NFT_NAME.to_string(),
+            symbol: PARAM_NFT_SYMBOL.to_string(),
+            icon: None,
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+
+    pub fn nft_total_supply(&self) -> U128 {
+        U128(Balance::from(self.token_list.len()))
+    }
+
+    pub fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<Token> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index = from_index.map_or(0, u128::from);
+        self.token_list
+            .keys()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .filter_map(|token_id| self.token(token_id))
+            .collect()
+    }
+
+    pub fn nft_supply_for_owner(&self, account_id: ValidAccountId) -> U128 {
+        U128(Balance::from(
+            self.token_owner_list(account_id.as_ref()).len(),
+        ))
+    }
+
+    pub fn nft_tokens_for_owner(
+        &self,
+        account_id: ValidAccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<Token> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index = from_index.map_or(0, u128::from);
+        self.token_owner_list(account_id.as_ref())
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .filter_map(|token_id| self.token(token_id))
+            .collect()
+    }
+
+    /// Members with voting rights, the lapsed ones are left out. Only a dues policy makes it walk
+    /// the members, so a member admitted or paying up during a vote counts at once
+    fn vote_total(&self) -> u64 {
+        if self.policy.dues.is_none() {
+            return self.member_list.len();
+        }
+        self.member_list
+            .iter()
+            .filter(|account_id| !self.is_lapsed(account_id))
+            .count() as u64
+    }
+
+    /// Dues are paid through this timestamp, a member who never paid starts from the later
+    /// of joining and enabling the dues
+    fn paid_through(&self, account_id: &AccountId) -> u64 {
+        match self.dues_list.get(account_id) {
+            Some(paid_through) => paid_through,
+            None => std::cmp::max(self.joined_at(account_id), self.dues_start),
+        }
+    }
+
+    fn is_lapsed(&self, account_id: &AccountId) -> bool {
+        match self.policy.dues.as_ref() {
+            Some(dues) => {
+                self.paid_through(account_id).saturating_add(dues.grace.0) < env::block_timestamp()
+            }
+            None => false,
+        }
+    }
+
+    fn assert_voter(&self, account_id: &AccountId) {
+        require(self.is_member(account_id.clone()), SocietyError::OnlyMember);
+        if self.is_lapsed(account_id) {
+            SocietyError::DuesLapsed(account_id.clone()).panic()
+        }
+    }
+
+    pub fn can_vote(self, proposal_id: u64, account_id: AccountId) -> bool {
+        if self.is_lapsed(&account_id) {
+            return false;
+        }
+        match self.vote_list.get(&proposal_id) {
+            Some(vote_list) => !vote_list.contains(&account_id),
+            None => true,
+        }
+    }
+
+    pub fn vote_approve(&mut self, proposal_id: u64) {
+        self.vote(proposal_id, true, false)
+    }
+
+    pub fn vote_reject(&mut self, proposal_id: u64) {
+        self.vote(proposal_id, false, false)
+    }
+
+    /// Reject the proposal as spam, the bond is slashed when most of the rejects are spam
+    pub fn vote_spam(&mut self, proposal_id: u64) {
+        self.vote(proposal_id, false, true)
+    }
+
+    fn vote(&mut self, proposal_id: u64, resolve: bool, spam: bool) {
+        let signer_account_id = env::signer_account_id();
+        self.assert_voter(&signer_account_id);
+        let mut proposal = match self.proposal_list.get(proposal_id) {
+            Some(proposal) => proposal,
+            None => SocietyError::ProposalNotFound.panic(),
+        };
+        if proposal.is_finalized() {
+            SocietyError::ProposalFinalized(proposal_id).panic()
+        }
+        if proposal.is_draft() {
+            SocietyError::ProposalNotInVote(proposal_id).panic()
+        }
+        let mut vote_list = match self.vote_list.get(&proposal_id) {
+            Some(vote_list) => vote_list,
+            None => UnorderedSet::new(StorageKey::ProposalVote {
+                hash: hash(format!("{}{}", proposal_id, proposal.author)),
+            }),
+        };
+        if vote_list.contains(&signer_account_id) {
+            SocietyError::AlreadyVoted.panic()
+        }
+        let initial_storage = env::storage_usage();
+        let status = proposal.status.clone();
+        proposal.vote(resolve, spam, self.vote_total());
+        log_event(
+            "vote_cast",
+            json!({
+                "proposal_id": proposal_id,
+                "account_id": signer_account_id,
+                "vote": match (resolve, spam) {
+                    (true, _) => "approve",
+                    (false, false) => "reject",
+                    (false, true) => "spam",
+                },
+            }),
+        );
+        if proposal.status != status {
+            log_event(
+                "proposal_status_changed",
+                json!({
+                    "proposal_id": proposal_id,
+                    "status": proposal.status,
+                }),
+            );
+        }
+        vote_list.insert(&signer_account_id);
+        self.vote_list.insert(&proposal_id, &vote_list);
+        self.charge_storage(&signer_account_id, initial_storage);
+        if proposal.is_draft() {
+            self.clear_votes(proposal_id);
+            self.fund_proposal -= proposal.fund();
+        }
+        if proposal.is_accepted() {
+            self.close_proposal(&proposal.author, proposal_id);
+            self.fund_proposal -= proposal.release();
+            self.release_bond(&proposal, true);
+            self.execute(proposal_id, &proposal);
+        };
+        if proposal.is_rejected() {
+            self.close_proposal(&proposal.author, proposal_id);
+            self.fund_proposal -= proposal.fund();
+            self.release_bond(&proposal, !proposal.vote.is_spam());
+        };
+        self.proposal_list.replace(proposal_id, &proposal);
+    }
+
+    /// Clear the votes and the body of a finalized proposal, each account gets back the storage
+    /// of its votes and comments, the rest goes to the author
+    pub fn archive_proposal(&mut self, proposal_id: u64) {
+        let mut proposal = match self.proposal_list.get(proposal_id) {
+            Some(proposal) => proposal,
+            None => SocietyError::ProposalNotFound.panic(),
+        };
+        if !proposal.is_finalized() {
+            SocietyError::ProposalNotFinalized(proposal_id).panic()
+        }
+        if proposal.archived {
+            SocietyError::ProposalArchived(proposal_id).panic()
+        }
+        let initial_storage = env::storage_usage();
+        let mut released = self.clear_votes(proposal_id);
+        if let Some(mut comment_list) = self.comment_list.remove(&proposal_id) {
+            loop {
+                let comment_storage = env::storage_usage();
+                match comment_list.pop() {
+                    Some(comment) => self.release_storage(&comment.author, comment_storage),
+                    None => break,
+                }
+                released += comment_storage - env::storage_usage();
+            }
+        }
+        if let Some(mut revision_list) = self.revision_list.remove(&proposal_id) {
+            revision_list.clear();
+        }
+        proposal.archive();
+        self.proposal_list.replace(proposal_id, &proposal);
+        self.release_storage(&proposal.author, initial_storage - released);
+        env::log(
+            format!(
+                "Proposal {} archived, {} bytes freed",
+                proposal_id,
+                initial_storage - env::storage_usage(),
+            )
+            .as_bytes(),
+        );
+    }
+
+    /// Drop the votes of the proposal, each voter gets back the storage of its vote and the
+    /// first one the storage of the list. Returns the bytes released to the voters
+    fn clear_votes(&mut self, proposal_id: u64) -> StorageUsage {
+        let initial_storage = env::storage_usage();
+        let mut vote_list = match self.vote_list.remove(&proposal_id) {
+            Some(vote_list) => vote_list,
+            None => return 0,
+        };
+        let voters = vote_list.to_vec();
+        if let Some(account_id) = voters.first() {
+            self.release_storage(account_id, initial_storage);
+        }
+        for account_id in voters {
+            let vote_storage = env::storage_usage();
+            vote_list.remove(&account_id);
+            self.release_storage(&account_id, vote_storage);
+        }
+        initial_storage - env::storage_usage()
+    }
+
+    fn take_bond(&mut self, proposal_id: u64, bond: Balance) {
+        let mut proposal = self.proposal_list.get(proposal_id).unwrap();
+        proposal.bond = bond;
+        self.fund_proposal += bond;
+        self.proposal_list.replace(proposal_id, &proposal);
+    }
+
+    /// The bond goes back to the author or stays in the treasury when slashed
+    fn release_bond(&mut self, proposal: &ProposalState, refund: bool) {
+        if proposal.bond == 0 {
+            return;
+        }
+        self.fund_proposal -= proposal.bond;
+        if refund {
+            self.payout(proposal.author.clone(), proposal.bond, "proposal_bond");
+        }
+    }
+
+    fn assert_bond(&self) -> Balance {
+        let bond = self.policy.proposal_bond.0;
+        require(
+            env::attached_deposit() == bond,
+            SocietyError::BondRequired(bond),
+        );
+        bond
+    }
+
+    fn payout(&self, receiver_id: AccountId, amount: Balance, source: &str) -> Promise {
+        log_event(
+            "payout",
+            json!({
+                "receiver_id": receiver_id,
+                "amount": U128(amount),
+                "source": source,
+            }),
+        );
+        Promise::new(receiver_id).transfer(amount)
+    }
+
+    /// The accepted proposal no longer applies to the current state, it stays accepted and
+    /// nothing is executed
+    fn skip_execution(&self, proposal_id: u64, error: SocietyError) {
+        log_event(
+            "proposal_execution_skipped",
+            json!({
+                "proposal_id": proposal_id,
+                "error": error.code(),
+                "message": error.to_string(),
+            }),
+        );
+    }
+
+    fn execute(&mut self, proposal_id: u64, proposal: &ProposalState) {
+        match proposal.kind {
+            ProposalKind::MemberRequest => {
+                self.add_member(proposal.author.clone());
+            }
+            ProposalKind::FundRequest => {
+                self.payout(proposal.author.clone(), proposal.fund(), "fund_request");
+            }
+            ProposalKind::FunctionCall => {
+                proposal.script::<FunctionCallScript>().promise();
+            }
+            ProposalKind::StakeRequest
+            | ProposalKind::UnstakeRequest
+            | ProposalKind::WithdrawRequest => {
+                let stake_script = proposal.script::<StakeScript>();
+                let pool_id: AccountId = stake_script.pool_id.into();
+                let amount = stake_script.amount;
+                // concurrent proposals on the pool may have taken the amount already
+                let mut stake = self.stake(&pool_id);
+                match proposal.kind {
+                    ProposalKind::UnstakeRequest => {
+                        if stake.staked < amount.0 {
+                            return self.skip_execution(proposal_id, SocietyError::NotEnoughStaked);
+                        }
+                        stake.staked -= amount.0;
+                        stake.unstaking += amount.0;
+                    }
+                    ProposalKind::WithdrawRequest => {
+                        if stake.unstaking < amount.0 {
+                            return self
+                                .skip_execution(proposal_id, SocietyError::NotEnoughUnstaking);
+                        }
+                        stake.unstaking -= amount.0;
+                    }
+                    _ => {}
+                }
+                self.set_stake(&pool_id, &stake);
+                let promise = match proposal.kind {
+                    ProposalKind::StakeRequest => ext_staking_pool::deposit_and_stake(
+                        &pool_id,
+                        amount.0,
+                        PARAM_GAS_STAKING_POOL,
+                    ),
+                    ProposalKind::UnstakeRequest => {
+                        ext_staking_pool::unstake(amount, &pool_id, 0, PARAM_GAS_STAKING_POOL)
+                    }
+                    _ => ext_staking_pool::withdraw(amount, &pool_id, 0, PARAM_GAS_STAKING_POOL),
+                };
+                promise.then(ext_self::on_staking_pool(
+                    pool_id,
+                    proposal.kind.clone(),
+                    amount,
+                    &env::current_account_id(),
+                    0,
+                    PARAM_GAS_CALLBACK,
+                ));
+            }
+            ProposalKind::StreamRequest => {
+                let stream_script = proposal.script::<StreamScript>();
+                let start = u64::from(stream_script.start);
+                self.stream_list.push(&StreamState {
+                    proposal_id,
+                    receiver: proposal.author.clone(),
+                    total: stream_script.total.into(),
+                    claimed: 0,
+                    start,
+                    end: stream_script.end.into(),
+                    cliff: stream_script.cliff.map_or(start, u64::from),
+                    canceled: None,
+                });
+            }
+            ProposalKind::StreamCancel => {
+                let stream_id = u64::from(proposal.script::<StreamCancelScript>().stream_id);
+                let mut stream = self.stream_state(stream_id);
+                if stream.canceled.is_some() {
+                    return self.skip_execution(proposal_id, SocietyError::StreamCanceled);
+                }
+                let timestamp = env::block_timestamp();
+                self.fund_proposal -= stream.total - stream.vested(timestamp);
+                stream.canceled = Some(timestamp);
+                self.stream_list.replace(stream_id, &stream);
+            }
+            ProposalKind::MilestoneGrant => {
+                let milestones = proposal
+                    .script::<MilestoneGrantScript>()
+                    .milestones
+                    .into_iter()
+                    .map(|milestone| Milestone {
+                        title: milestone.title,
+                        amount: milestone.amount,
+                        status: MilestoneStatus::Pending,
+                        deliverable: None,
+                        vote: ProposalVote {
+                            approve: 0,
+                            reject: 0,
+                            spam: 0,
+                        },
+                    })
+                    .collect();
+                self.grant_list.push(&GrantState {
+                    proposal_id,
+                    receiver: proposal.author.clone(),
+                    milestones,
+                });
+            }
+            ProposalKind::GrantCancel => {
+                let grant_id = u64::from(proposal.script::<GrantCancelScript>().grant_id);
+                let mut grant = self.grant_state(grant_id);
+                for (milestone_id, milestone) in grant.milestones.iter_mut().enumerate() {
+                    // a concurrent cancel may have released the milestones already
+                    if milestone.status == MilestoneStatus::Paid
+                        || milestone.status == MilestoneStatus::Canceled
+                    {
+                        continue;
+                    }
+                    self.fund_proposal -= milestone.amount.0;
+                    milestone.status = MilestoneStatus::Canceled;
+                    self.clear_milestone_votes(grant_id, milestone_id as u64);
+                }
+                self.grant_list.replace(grant_id, &grant);
+            }
+            ProposalKind::Bounty => {
+                let bounty_script = proposal.script::<BountyScript>();
+                self.bounty_list.push(&BountyState {
+                    proposal_id,
+                    reward: bounty_script.reward,
+                    deadline: bounty_script.deadline,
+                    claim: None,
+                    done: false,
+                    canceled: false,
+                });
+            }
+            ProposalKind::BountyDone => {
+                let bounty_id = u64::from(proposal.script::<BountyDoneScript>().bounty_id);
+                let mut bounty = self.bounty_state(bounty_id);
+                let claim = match bounty.claim.take() {
+                    Some(claim) if claim.proposal_id == Some(proposal_id) => claim,
+                    _ => return,
+                };
+                let payout = bounty.reward.0 + claim.bond.0;
+                self.fund_proposal -= payout;
+                bounty.done = true;
+                bounty.claim = Some(claim);
+                self.bounty_list.replace(bounty_id, &bounty);
+                self.payout(proposal.author.clone(), payout, "bounty");
+            }
+            ProposalKind::BountyCancel => {
+                let bounty_id = u64::from(proposal.script::<BountyCancelScript>().bounty_id);
+                let mut bounty = self.bounty_state(bounty_id);
+                if bounty.done {
+                    return self.skip_execution(proposal_id, SocietyError::BountyDone);
+                }
+                if self.is_claim_active(&bounty) {
+                    return self.skip_execution(proposal_id, SocietyError::BountyClaimed);
+                }
+                self.expire_claim(bounty_id, &mut bounty);
+                self.fund_proposal -= bounty.reward.0;
+                bounty.done = true;
+                bounty.canceled = true;
+                self.bounty_list.replace(bounty_id, &bounty);
+            }
+            ProposalKind::PolicyUpdate => {
+                let policy = proposal.script::<Policy>();
+                if self.policy.dues.is_none() && policy.dues.is_some() {
+                    self.dues_start = env::block_timestamp();
+                }
+                self.policy = policy;
+                log_event("policy_changed", json!({ "policy": self.policy }));
+            }
+            ProposalKind::ArtMint => {
+                self.mint_art(proposal.script::<ArtMintScript>());
+            }
+            ProposalKind::AcquireNft => {
+                let acquire_script = proposal.script::<AcquireNftScript>();
+                let market_id = match self.policy.market_id.clone() {
+                    Some(market_id) => market_id,
+                    None => {
+                        return self.skip_execution(proposal_id, SocietyError::MarketNotConfigured)
+                    }
+                };
+                let nft_contract_id: AccountId = acquire_script.nft_contract_id.into();
+                let acquisition_id = self.acquisition_list.len();
+                self.acquisition_index.insert(
+                    &nft_key(&nft_contract_id, &acquire_script.token_id),
+                    &acquisition_id,
+                );
+                let args = json!({
+                    "nft_contract_id": nft_contract_id,
+                    "token_id": acquire_script.token_id,
+                });
+                self.acquisition_list.push(&Acquisition {
+                    proposal_id,
+                    nft_contract_id,
+                    token_id: acquire_script.token_id,
+                    price: acquire_script.price,
+                    status: AcquisitionStatus::Pending,
+                    timestamp: U64(env::block_timestamp()),
+                });
+                Promise::new(market_id)
+                    .function_call(
+                        self.policy.market_method.clone().into_bytes(),
+                        args.to_string().into_bytes(),
+                        acquire_script.price.0,
+                        PARAM_GAS_MARKET,
+                    )
+                    .then(ext_self::on_acquire_nft(
+                        acquisition_id,
+                        &env::current_account_id(),
+                        0,
+                        PARAM_GAS_CALLBACK,
+                    ));
+            }
+            ProposalKind::AuctionArtwork => {
+                let auction_script = proposal.script::<AuctionScript>();
+                let nft_contract_id: AccountId = match auction_script.nft_contract_id {
+                    Some(nft_contract_id) => nft_contract_id.into(),
+                    None => env::current_account_id(),
+                };
+                let auction_id = self.auction_list.len();
+                self.auction_index.insert(
+                    &nft_key(&nft_contract_id, &auction_script.token_id),
+                    &auction_id,
+                );
+                self.auction_list.push(&Auction {
+                    proposal_id,
+                    nft_contract_id,
+                    token_id: auction_script.token_id,
+                    reserve_price: auction_script.reserve_price,
+                    min_increment: auction_script.min_increment,
+                    end: U64(env::block_timestamp().saturating_add(auction_script.duration.0)),
+                    bid: None,
+                    settled: false,
+                });
+            }
+            ProposalKind::Exhibition => {
+                let exhibition_script = proposal.script::<ExhibitionScript>();
+                self.exhibition_list.push(&ExhibitionState {
+                    proposal_id,
+                    title: exhibition_script.title,
+                    description: exhibition_script.description,
+                    curator: exhibition_script.curator.into(),
+                    items: exhibition_script.items,
+                    open: exhibition_script.open.into(),
+                    close: exhibition_script.close.into(),
+                });
+            }
+            ProposalKind::MemberRemove => {
+                let account_id = proposal.script::<MemberRemoveScript>().account_id;
+                if self.member_list.len() > 1 {
+                    self.remove_member(account_id.into());
+                }
+            }
+            ProposalKind::CommentHide => {
+                let comment_hide_script = proposal.script::<CommentHideScript>();
+                let target_id = comment_hide_script.proposal_id.into();
+                let comment_id = comment_hide_script.comment_id.into();
+                let mut comment_list = self.comment_list(target_id);
+                if let Some(mut comment) = comment_list.get(comment_id) {
+                    comment.hidden = true;
+                    comment_list.replace(comment_id, &comment);
+                    log_event(
+                        "comment_hidden",
+                        json!({
+                            "proposal_id": target_id,
+                            "comment_id": comment_id,
+                        }),
+                    );
+                }
+            }
+        }
+    }
+
+    fn bounty_state(&self, bounty_id: u64) -> BountyState {
+        match self.bounty_list.get(bounty_id) {
+            Some(bounty) => bounty,
+            None => SocietyError::BountyNotFound.panic(),
+        }
+    }
+
+    /// Drop the expired claim of the bounty, the bond is forfeited or returned per policy
+    /// The claim is still running or its work is in vote
+    fn is_claim_active(&self, bounty: &BountyState) -> bool {
+        let claim = match bounty.claim.as_ref() {
+            Some(claim) => claim,
+            None => return false,
+        };
+        let in_vote = match claim.proposal_id.and_then(|id| self.proposal_list.get(id)) {
+            Some(proposal) => proposal.status == ProposalStatus::Vote,
+            None => false,
+        };
+        !bounty.is_expired(claim) || in_vote
+    }
+
+    /// Drop the expired claim, the claimant gets back the storage of the claim
+    fn expire_claim(&mut self, bounty_id: u64, bounty: &mut BountyState) {
+        if self.is_claim_active(bounty) {
+            SocietyError::BountyClaimed.panic()
+        }
+        let initial_storage = env::storage_usage();
+        let claim = match bounty.claim.take() {
+            Some(claim) => claim,
+            None => return,
+        };
+        self.bounty_list.replace(bounty_id, bounty);
+        self.release_storage(&claim.account_id, initial_storage);
+        self.fund_proposal -= claim.bond.0;
+        if !self.policy.bounty_forfeit {
+            self.payout(claim.account_id, claim.bond.0, "bounty_bond");
+        }
+    }
+
+    #[payable]
+    pub fn claim_bounty(&mut self, bounty_id: u64) {
+        let mut bounty = self.bounty_state(bounty_id);
+        if bounty.done {
+            SocietyError::BountyDone.panic()
+        }
+        let bond = self.policy.bounty_bond;
+        require(
+            env::attached_deposit() == bond.0,
+            SocietyError::BondRequired(bond.0),
+        );
+        self.expire_claim(bounty_id, &mut bounty);
+        self.fund_proposal += bond.0;
+        let account_id = env::predecessor_account_id();
+        let initial_storage = env::storage_usage();
+        bounty.claim = Some(BountyClaim {
+            account_id: account_id.clone(),
+            timestamp: U64(env::block_timestamp()),
+            bond,
+            submission: None,
+            proposal_id: None,
+        });
+        self.bounty_list.replace(bounty_id, &bounty);
+        self.charge_storage(&account_id, initial_storage);
+    }
+
+    pub fn expire_bounty_claim(&mut self, bounty_id: u64) {
+        let mut bounty = self.bounty_state(bounty_id);
+        if bounty.done {
+            SocietyError::BountyDone.panic()
+        }
+        self.expire_claim(bounty_id, &mut bounty);
+    }
+
+    pub fn submit_bounty_work(&mut self, bounty_id: u64, submission: String) {
+        let mut bounty = self.bounty_state(bounty_id);
+        if submission.len() > 1000 {
+            SocietyError::FieldLength("submission", 1000).panic()
+        }
+        let expired = match bounty.claim.as_ref() {
+            Some(claim) if claim.account_id == env::predecessor_account_id() => {
+                bounty.is_expired(claim)
+            }
+            _ => SocietyError::OnlyClaimant.panic(),
+        };
+        if expired || bounty.done {
+            SocietyError::BountyExpired.panic()
+        }
+        let initial_storage = env::storage_usage();
+        if let Some(claim) = bounty.claim.as_mut() {
+            claim.submission = Some(submission);
+        }
+        self.bounty_list.replace(bounty_id, &bounty);
+        self.charge_storage(&env::predecessor_account_id(), initial_storage);
+    }
+
+    fn grant_state(&self, grant_id: u64) -> GrantState {
+        match self.grant_list.get(grant_id) {
+            Some(grant) => grant,
+            None => SocietyError::GrantNotFound.panic(),
+        }
+    }
+
+    fn milestone_vote_list(&self, grant_id: u64, milestone_id: u64) -> UnorderedSet<AccountId> {
+        match self.milestone_vote_list.get(&(grant_id, milestone_id)) {
+            Some(vote_list) => vote_list,
+            None => UnorderedSet::new(StorageKey::MilestoneVote {
+                hash: hash(format!("{}:{}", grant_id, milestone_id)),
+            }),
+        }
+    }
+
+    pub fn submit_milestone(&mut self, grant_id: u64, milestone_id: u64, deliverable: String) {
+        let mut grant = self.grant_state(grant_id);
+        require(
+            grant.receiver == env::predecessor_account_id(),
+            SocietyError::OnlyReceiver,
+        );
+        if deliverable.len() > 1000 {
+            SocietyError::FieldLength("deliverable", 1000).panic()
+        }
+        let milestone = match grant.milestones.get_mut(milestone_id as usize) {
+            Some(milestone) => milestone,
+            None => SocietyError::MilestoneNotFound.panic(),
+        };
+        if milestone.status != MilestoneStatus::Pending {
+            SocietyError::MilestoneNotPending.panic()
+        }
+        milestone.status = MilestoneStatus::Review;
+        milestone.deliverable = Some(deliverable);
+        self.grant_list.replace(grant_id, &grant);
+    }
+
+    pub fn vote_milestone(&mut self, grant_id: u64, milestone_id: u64, resolve: bool) {
+        let signer_account_id = env::signer_account_id();
+        self.assert_voter(&signer_account_id);
+        let mut grant = self.grant_state(grant_id);
+        let milestone = match grant.milestones.get_mut(milestone_id as usize) {
+            Some(milestone) => milestone,
+            None => SocietyError::MilestoneNotFound.panic(),
+        };
+        if milestone.status != MilestoneStatus::Review {
+            SocietyError::MilestoneNotReview.panic()
+        }
+        let initial_storage = env::storage_usage();
+        let mut vote_list = self.milestone_vote_list(grant_id, milestone_id);
+        if !vote_list.insert(&signer_account_id) {
+            SocietyError::AlreadyVoted.panic()
+        }
+        self.milestone_vote_list
+            .insert(&(grant_id, milestone_id), &vote_list);
+        self.charge_storage(&signer_account_id, initial_storage);
+        if resolve {
+            milestone.vote.approve += 1;
+        } else {
+            milestone.vote.reject += 1;
+        }
+        let quorum = milestone.vote.approve + milestone.vote.reject;
+        let total = self.vote_total();
+        let is_resolved = consensus(total, quorum) && !milestone.vote.is_parte();
+        if is_resolved && milestone.vote.is_approve() {
+            milestone.status = MilestoneStatus::Paid;
+            self.fund_proposal -= milestone.amount.0;
+            self.payout(grant.receiver.clone(), milestone.amount.0, "milestone");
+        } else if is_resolved || quorum >= total {
+            milestone.status = MilestoneStatus::Pending;
+        }
+        if milestone.status != MilestoneStatus::Review {
+            milestone.vote = ProposalVote {
+                approve: 0,
+                reject: 0,
+                spam: 0,
+            };
+            self.clear_milestone_votes(grant_id, milestone_id);
+        }
+        self.grant_list.replace(grant_id, &grant);
+    }
+
+    /// Drop the votes of the milestone, each voter gets back the storage of its vote and the
+    /// first one the storage of the list
+    fn clear_milestone_votes(&mut self, grant_id: u64, milestone_id: u64) {
+        let initial_storage = env::storage_usage();
+        let mut vote_list = match self.milestone_vote_list.remove(&(grant_id, milestone_id)) {
+            Some(vote_list) => vote_list,
+            None => return,
+        };
+        let voters = vote_list.to_vec();
+        if let Some(account_id) = voters.first() {
+            self.release_storage(account_id, initial_storage);
+        }
+        for account_id in voters {
+            let vote_storage = env::storage_usage();
+            vote_list.remove(&account_id);
+            self.release_storage(&account_id, vote_storage);
+        }
+    }
+
+    fn stream_state(&self, stream_id: u64) -> StreamState {
+        match self.stream_list.get(stream_id) {
+            Some(stream) => stream,
+            None => SocietyError::StreamNotFound.panic(),
+        }
+    }
+
+    pub fn claim_stream(&mut self, stream_id: u64) -> U128 {
+        let mut stream = self.stream_state(stream_id);
+        require(
+            stream.receiver == env::predecessor_account_id(),
+            SocietyError::OnlyReceiver,
+        );
+        let amount = stream.claimable();
+        require(amount != 0, SocietyError::NothingToClaim);
+        stream.claimed += amount;
+        self.stream_list.replace(stream_id, &stream);
+        self.fund_proposal -= amount;
+        self.payout(stream.receiver, amount, "stream");
+        U128(amount)
+    }
+
+    #[payable]
+    pub fn add_member_proposal(
+        &mut self,
+        title: Option<String>,
+        description: Option<String>,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> PromiseOrValue<u64> {
+        let signer_account_id = env::signer_account_id();
+        self.assert_is_member(signer_account_id.clone());
+        let bond = self.assert_bond();
+        self.assert_storage_available(&signer_account_id, PARAM_STORAGE_PROPOSAL);
+        self.assert_proposal_open(
+            &signer_account_id,
+            &ProposalKind::MemberRequest,
+            proposal_id,
+        );
+        // the callback cannot fail without losing the bond, so the text is checked up front
+        assert_proposal_text(
+            title.as_deref().unwrap_or_default(),
+            description.as_deref().unwrap_or_default(),
+        );
+        if let Some(attachments) = &attachments {
+            assert_attachments(attachments);
+        }
+        match self.policy.member_nft_contract_id.clone() {
+            Some(nft_contract_id) => ext_nft::nft_tokens_for_owner(
+                signer_account_id.clone(),
+                None,
+                Some(1),
+                &nft_contract_id,
+                0,
+                PARAM_GAS_NFT_VIEW,
+            )
+            .then(ext_self::on_member_nft(
+                MemberApplication {
+                    account_id: signer_account_id,
+                    title,
+                    description,
+                    bond: U128(bond),
+                    proposal_id,
+                    attachments,
+                },
+                &env::current_account_id(),
+                0,
+                PARAM_GAS_CALLBACK,
+            ))
+            .into(),
+            None => {
+                let proposal_id = self.add_proposal(
+                    signer_account_id,
+                    ProposalKind::MemberRequest,
+                    proposal_id,
+                    title,
+                    description,
+                    None,
+                    attachments,
+                );
+                self.take_bond(proposal_id, bond);
+                PromiseOrValue::Value(proposal_id)
+            }
+        }
+    }
+
+    /// Record the member request once the applicant holds a token of the collection and can
+    /// still open it, otherwise the bond is returned
+    #[private]
+    pub fn on_member_nft(&mut self, application: MemberApplication) -> Option<u64> {
+        let MemberApplication {
+            account_id,
+            title,
+            description,
+            bond,
+            proposal_id,
+            attachments,
+        } = application;
+        let nft_contract_id = self
+            .policy
+            .member_nft_contract_id
+            .clone()
+            .unwrap_or_default();
+        let token_list: Vec<NftTokenId> = match env::promise_result(0) {
+            PromiseResult::Successful(value) => serde_json::from_slice(&value).unwrap_or_default(),
+            _ => vec![],
+        };
+        let token_id = match token_list.into_iter().next() {
+            Some(token) if !self.is_member(account_id.clone()) => token.token_id,
+            _ => {
+                env::log(format!("Applicant must hold a token of {}", nft_contract_id).as_bytes());
+                self.refund_application(account_id, bond.0);
+                return None;
+            }
+        };
+        // the proposals or the storage of the applicant may have changed since the request
+        let error = self
+            .proposal_open_error(&account_id, &ProposalKind::MemberRequest, proposal_id)
+            .or_else(|| self.storage_error(&account_id, PARAM_STORAGE_PROPOSAL));
+        if let Some(error) = error {
+            env::log(format!("{}: {}", error.code(), error).as_bytes());
+            self.refund_application(account_id, bond.0);
+            return None;
+        }
+        let script = json!(MemberScript {
+            nft_contract_id,
+            token_id,
+        });
+        let proposal_id = self.add_proposal(
+            account_id,
+            ProposalKind::MemberRequest,
+            proposal_id,
+            title,
+            description,
+            Some(script.to_string()),
+            attachments,
+        );
+        self.take_bond(proposal_id, bond.0);
+        Some(proposal_id)
+    }
+
+    fn refund_application(&self, account_id: AccountId, bond: Balance) {
+        if bond > 0 {
+            self.payout(account_id, bond, "proposal_bond");
+        }
+    }
+
+    #[payable]
+    pub fn add_fund_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let bond = self.assert_bond();
+        let fund_script: FundScript = parse_script(&script);
+        let request_fund = u128::from(fund_script.fund);
+        if request_fund >= self.fund() {
+            SocietyError::NotEnoughFund.panic()
+        };
+        self.fund_proposal += request_fund;
+        let proposal_id = self.add_proposal(
+            signer_account_id,
+            ProposalKind::FundRequest,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        );
+        self.take_bond(proposal_id, bond);
+        proposal_id
+    }
+
+    pub fn add_function_call_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let function_call_script: FunctionCallScript = parse_script(&script);
+        require(
+            !function_call_script.actions.is_empty(),
+            SocietyError::ActionRequired,
+        );
+        let request_fund = function_call_script.deposit();
+        if request_fund >= self.fund() {
+            SocietyError::NotEnoughFund.panic()
+        };
+        self.fund_proposal += request_fund;
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::FunctionCall,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_stake_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        self.add_staking_pool_proposal(
+            ProposalKind::StakeRequest,
+            title,
+            description,
+            script,
+            proposal_id,
+            attachments,
+        )
+    }
+
+    pub fn add_unstake_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        self.add_staking_pool_proposal(
+            ProposalKind::UnstakeRequest,
+            title,
+            description,
+            script,
+            proposal_id,
+            attachments,
+        )
+    }
+
+    pub fn add_withdraw_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        self.add_staking_pool_proposal(
+            ProposalKind::WithdrawRequest,
+            title,
+            description,
+            script,
+            proposal_id,
+            attachments,
+        )
+    }
+
+    fn add_staking_pool_proposal(
+        &mut self,
+        kind: ProposalKind,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let stake_script: StakeScript = parse_script(&script);
+        let amount = u128::from(stake_script.amount);
+        require(amount != 0, SocietyError::ZeroValue("amount"));
+        let stake = self.stake(stake_script.pool_id.as_ref());
+        match kind {
+            ProposalKind::StakeRequest => {
+                if amount >= self.fund() {
+                    SocietyError::NotEnoughFund.panic()
+                };
+                self.fund_proposal += amount;
+            }
+            ProposalKind::UnstakeRequest => {
+                if amount > stake.staked {
+                    SocietyError::NotEnoughStaked.panic()
+                };
+            }
+            _ => {
+                if amount > stake.unstaking {
+                    SocietyError::NotEnoughUnstaking.panic()
+                };
+            }
+        }
+        self.add_proposal(
+            signer_account_id,
+            kind,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_stream_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let stream_script: StreamScript = parse_script(&script);
+        let request_fund = u128::from(stream_script.total);
+        let start = u64::from(stream_script.start);
+        let end = u64::from(stream_script.end);
+        require(request_fund != 0, SocietyError::ZeroValue("total"));
+        require(start < end, SocietyError::StreamSchedule);
+        if let Some(cliff) = stream_script.cliff {
+            require(
+                start <= cliff.0 && cliff.0 <= end,
+                SocietyError::StreamCliff,
+            );
+        }
+        if request_fund >= self.fund() {
+            SocietyError::NotEnoughFund.panic()
+        };
+        self.fund_proposal += request_fund;
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::StreamRequest,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_stream_cancel_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let stream_cancel_script: StreamCancelScript = parse_script(&script);
+        let stream = self.stream_state(stream_cancel_script.stream_id.into());
+        require(stream.canceled.is_none(), SocietyError::StreamCanceled);
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::StreamCancel,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_milestone_grant_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let grant_script: MilestoneGrantScript = parse_script(&script);
+        require(
+            !grant_script.milestones.is_empty(),
+            SocietyError::MilestoneRequired,
+        );
+        for milestone in grant_script.milestones.iter() {
+            require(milestone.amount.0 != 0, SocietyError::ZeroValue("amount"));
+            if milestone.title.len() > 170 {
+                SocietyError::FieldLength("milestone title", 170).panic()
+            }
+        }
+        let request_fund = grant_script.total();
+        if request_fund >= self.fund() {
+            SocietyError::NotEnoughFund.panic()
+        };
+        self.fund_proposal += request_fund;
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::MilestoneGrant,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_grant_cancel_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let grant_cancel_script: GrantCancelScript = parse_script(&script);
+        let grant = self.grant_state(grant_cancel_script.grant_id.into());
+        require(
+            grant.milestones.iter().any(|milestone| {
+                milestone.status == MilestoneStatus::Pending
+                    || milestone.status == MilestoneStatus::Review
+            }),
+            SocietyError::GrantPaid,
+        );
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::GrantCancel,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_bounty_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let bounty_script: BountyScript = parse_script(&script);
+        let request_fund = u128::from(bounty_script.reward);
+        require(request_fund != 0, SocietyError::ZeroValue("reward"));
+        require(
+            bounty_script.deadline.0 != 0,
+            SocietyError::ZeroValue("deadline"),
+        );
+        if request_fund >= self.fund() {
+            SocietyError::NotEnoughFund.panic()
+        };
+        self.fund_proposal += request_fund;
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::Bounty,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_bounty_done_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        let bounty_done_script: BountyDoneScript = parse_script(&script);
+        let bounty_id = u64::from(bounty_done_script.bounty_id);
+        let mut bounty = self.bounty_state(bounty_id);
+        let has_submission = match bounty.claim.as_ref() {
+            Some(claim) if claim.account_id == signer_account_id => claim.submission.is_some(),
+            _ => SocietyError::OnlyClaimant.panic(),
+        };
+        if !has_submission {
+            SocietyError::BountyNoSubmission.panic()
+        }
+        let proposal_id = self.add_proposal(
+            signer_account_id,
+            ProposalKind::BountyDone,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        );
+        if let Some(claim) = bounty.claim.as_mut() {
+            claim.proposal_id = Some(proposal_id);
+        }
+        self.bounty_list.replace(bounty_id, &bounty);
+        proposal_id
+    }
+
+    /// Release the reward of a bounty nobody works on, an expired claim is dropped on execution
+    pub fn add_bounty_cancel_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let bounty_cancel_script: BountyCancelScript = parse_script(&script);
+        let bounty = self.bounty_state(bounty_cancel_script.bounty_id.into());
+        require(!bounty.done, SocietyError::BountyDone);
+        if let Some(claim) = bounty.claim.as_ref() {
+            require(bounty.is_expired(claim), SocietyError::BountyClaimed);
+        }
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::BountyCancel,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_member_remove_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let member_remove_script: MemberRemoveScript = parse_script(&script);
+        let account_id: AccountId = member_remove_script.account_id.into();
+        if !self.is_member(account_id.clone()) {
+            SocietyError::NotMember(account_id).panic()
+        }
+        require(self.member_list.len() > 1, SocietyError::LastMember);
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::MemberRemove,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_comment_hide_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let comment_hide_script: CommentHideScript = parse_script(&script);
+        let comment = self
+            .comment_list(comment_hide_script.proposal_id.into())
+            .get(comment_hide_script.comment_id.into());
+        match comment {
+            Some(comment) if !comment.hidden => {}
+            Some(_) => SocietyError::CommentHidden.panic(),
+            None => SocietyError::CommentNotFound.panic(),
+        }
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::CommentHide,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_art_mint_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let art_mint_script: ArtMintScript = parse_script(&script);
+        if art_mint_script.title.is_empty() || art_mint_script.title.len() > 170 {
+            SocietyError::FieldLength("art title", 170).panic()
+        }
+        if art_mint_script.media.is_empty() || art_mint_script.media.len() > 1000 {
+            SocietyError::FieldLength("art media", 1000).panic()
+        }
+        if art_mint_script.media_hash.0.len() != 32 {
+            SocietyError::InvalidField("art media hash", "must be sha256".to_string()).panic()
+        }
+        let copies = art_mint_script.copies.unwrap_or(1);
+        if copies == 0 || copies > PARAM_ART_COPIES_MAX {
+            SocietyError::InvalidField(
+                "art copies",
+                format!("must be between 1 and {}", PARAM_ART_COPIES_MAX),
+            )
+            .panic()
+        }
+        let royalty = art_mint_script.royalty.unwrap_or_default();
+        if royalty.len() > PARAM_ROYALTY_LEN_MAX {
+            SocietyError::InvalidField(
+                "art royalty",
+                format!("must have less {} receivers", PARAM_ROYALTY_LEN_MAX),
+            )
+            .panic()
+        }
+        for account_id in royalty.keys() {
+            if !env::is_valid_account_id(account_id.as_bytes()) {
+                SocietyError::InvalidAccount(account_id.clone()).panic()
+            }
+        }
+        if royalty.values().sum::<u32>() > PARAM_ROYALTY_MAX {
+            SocietyError::InvalidField(
+                "art royalty",
+                format!("must be less {} basis points", PARAM_ROYALTY_MAX),
+            )
+            .panic()
+        }
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::ArtMint,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_acquire_nft_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        if self.policy.market_id.is_none() {
+            SocietyError::MarketNotConfigured.panic()
+        }
+        let acquire_script: AcquireNftScript = parse_script(&script);
+        let key = nft_key(
+            acquire_script.nft_contract_id.as_ref(),
+            &acquire_script.token_id,
+        );
+        if self.acquisition_index.get(&key).is_some() {
+            SocietyError::AcquisitionPending.panic()
+        }
+        let request_fund = u128::from(acquire_script.price);
+        require(request_fund != 0, SocietyError::ZeroValue("price"));
+        if request_fund >= self.fund() {
+            SocietyError::NotEnoughFund.panic()
+        };
+        self.fund_proposal += request_fund;
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::AcquireNft,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_auction_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let auction_script: AuctionScript = parse_script(&script);
+        let nft_contract_id: AccountId = match auction_script.nft_contract_id.as_ref() {
+            Some(nft_contract_id) => nft_contract_id.to_string(),
+            None => env::current_account_id(),
+        };
+        if nft_contract_id == env::current_account_id() {
+            match self.token_list.get(&auction_script.token_id) {
+                Some(token) if token.owner_id == nft_contract_id => {}
+                _ => SocietyError::TokenNotOwned.panic(),
+            }
+        }
+        let key = nft_key(&nft_contract_id, &auction_script.token_id);
+        if self.auction_index.get(&key).is_some() {
+            SocietyError::AuctionOpen.panic()
+        }
+        require(
+            auction_script.duration.0 != 0,
+            SocietyError::ZeroValue("duration"),
+        );
+        require(
+            auction_script.duration.0 <= PARAM_AUCTION_DURATION_MAX,
+            SocietyError::AuctionDuration(PARAM_AUCTION_DURATION_MAX),
+        );
+        require(
+            auction_script.min_increment.0 != 0,
+            SocietyError::ZeroValue("min increment"),
+        );
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::AuctionArtwork,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_exhibition_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let exhibition_script: ExhibitionScript = parse_script(&script);
+        if exhibition_script.title.is_empty() || exhibition_script.title.len() > 170 {
+            SocietyError::FieldLength("exhibition title", 170).panic()
+        }
+        if exhibition_script.description.len() > 1000 {
+            SocietyError::FieldLength("exhibition description", 1000).panic()
+        }
+        if exhibition_script.items.len() > PARAM_EXHIBITION_ITEMS_MAX {
+            SocietyError::InvalidField(
+                "exhibition items",
+                format!("must have less {} items", PARAM_EXHIBITION_ITEMS_MAX),
+            )
+            .panic()
+        }
+        for item in exhibition_script.items.iter() {
+            if !env::is_valid_account_id(item.nft_contract_id.as_bytes()) {
+                SocietyError::InvalidAccount(item.nft_contract_id.clone()).panic()
+            }
+        }
+        require(
+            exhibition_script.open.0 < exhibition_script.close.0,
+            SocietyError::ExhibitionSchedule,
+        );
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::Exhibition,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    pub fn add_policy_proposal(
+        &mut self,
+        title: String,
+        description: String,
+        script: String,
+        proposal_id: Option<u64>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let policy: Policy = parse_script(&script);
+        if let Some(dues) = policy.dues {
+            require(dues.amount.0 != 0, SocietyError::ZeroValue("dues amount"));
+            require(dues.period.0 != 0, SocietyError::ZeroValue("dues period"));
+            require(
+                dues.period.0 <= PARAM_DUES_PERIOD_MAX && dues.grace.0 <= PARAM_DUES_PERIOD_MAX,
+                SocietyError::DuesPeriod(PARAM_DUES_PERIOD_MAX),
+            );
+        }
+        self.add_proposal(
+            signer_account_id,
+            ProposalKind::PolicyUpdate,
+            proposal_id,
+            Some(title),
+            Some(description),
+            Some(script),
+            attachments,
+        )
+    }
+
+    fn assert_is_member(&mut self, account_id: AccountId) {
+        if self.member_list.contains(&account_id) {
+            SocietyError::AlreadyMember(account_id).panic()
+        }
+    }
+
+    /// Edit the title keeping the votes, the description and the attachments change only in the
+    /// draft status. A new script replaces the draft through its kind as `add_*_proposal` with
+    /// `proposal_id`, the deposit is the bond of a fund request and is refunded otherwise
+    #[payable]
+    pub fn update_proposal(
+        &mut self,
+        proposal_id: u64,
+        title: Option<String>,
+        description: Option<String>,
+        script: Option<String>,
+        attachments: Option<Vec<Attachment>>,
+    ) {
+        let signer_account_id = env::signer_account_id();
+        let mut proposal = match self.proposal_list.get(proposal_id) {
+            Some(proposal)
+                if proposal.author == signer_account_id
+                    && self
+                        .active_proposal_list(&signer_account_id)
+                        .contains(&proposal_id) =>
+            {
+                proposal
+            }
+            _ => SocietyError::ProposalNotActive(proposal_id).panic(),
+        };
+        let title = title.unwrap_or_else(|| proposal.title.clone());
+        let description = description.unwrap_or_else(|| proposal.description.clone());
+        match script {
+            Some(script) if Some(&script) != proposal.script.as_ref() => {
+                // only the fund request takes the deposit as the new bond
+                let refund = env::attached_deposit();
+                if refund > 0 && proposal.kind != ProposalKind::FundRequest {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+                self.update_proposal_script(
+                    proposal_id,
+                    &proposal.kind,
+                    title,
+                    description,
+                    script,
+                    attachments,
+                );
+            }
+            _ => {
+                assert_proposal_text(&title, &description);
+                let attachments = attachments.unwrap_or_else(|| proposal.attachments.clone());
+                assert_attachments(&attachments);
+                // the votes stand for the description and the attachments, so those change
+                // only in the draft status where no votes are left
+                if (description != proposal.description || attachments != proposal.attachments)
+                    && !proposal.is_draft()
+                {
+                    SocietyError::ProposalNotDraft(proposal_id).panic()
+                }
+                let refund = env::attached_deposit();
+                if refund > 0 {
+                    Promise::new(env::predecessor_account_id()).transfer(refund);
+                }
+                let initial_storage = env::storage_usage();
+                self.push_revision(proposal_id, &proposal);
+                proposal.title = title;
+                proposal.description = description;
+                proposal.attachments = attachments;
+                self.proposal_list.replace(proposal_id, &proposal);
+                log_event(
+                    "proposal_updated",
+                    json!({
+                        "proposal_id": proposal_id,
+                        "author": signer_account_id,
+                        "kind": proposal.kind,
+                    }),
+                );
+                self.charge_storage(&signer_account_id, initial_storage);
+            }
+        }
+    }
+
+    fn update_proposal_script(
+        &mut self,
+        proposal_id: u64,
+        kind: &ProposalKind,
+        title: String,
+        description: String,
+        script: String,
+        attachments: Option<Vec<Attachment>>,
+    ) {
+        let proposal_id = Some(proposal_id);
+        match kind {
+            ProposalKind::MemberRequest => SocietyError::ProposalNoScript.panic(),
+            ProposalKind::FundRequest => {
+                self.add_fund_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::FunctionCall => {
+                self.add_function_call_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::StakeRequest => {
+                self.add_stake_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::UnstakeRequest => {
+                self.add_unstake_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::WithdrawRequest => {
+                self.add_withdraw_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::StreamRequest => {
+                self.add_stream_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::StreamCancel => {
+                self.add_stream_cancel_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::MilestoneGrant => {
+                self.add_milestone_grant_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::GrantCancel => {
+                self.add_grant_cancel_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::Bounty => {
+                self.add_bounty_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::BountyDone => {
+                self.add_bounty_done_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::BountyCancel => {
+                self.add_bounty_cancel_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::PolicyUpdate => {
+                self.add_policy_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::MemberRemove => {
+                self.add_member_remove_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::CommentHide => {
+                self.add_comment_hide_proposal(
+                    title,
+                    description,
+                    script,
+                    proposal_id,
+                    attachments,
+                );
+            }
+            ProposalKind::ArtMint => {
+                self.add_art_mint_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::AcquireNft => {
+                self.add_acquire_nft_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::AuctionArtwork => {
+                self.add_auction_proposal(title, description, script, proposal_id, attachments);
+            }
+            ProposalKind::Exhibition => {
+                self.add_exhibition_proposal(title, description, script, proposal_id, attachments);
+            }
+        }
+    }
+
+    /// Comment the proposal, the storage is paid by the commenter
+    pub fn add_comment(&mut self, proposal_id: u64, text: String, reply_to: Option<u64>) -> u64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.policy.comment_open || self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        match self.proposal_list.get(proposal_id) {
+            Some(proposal) if !proposal.archived => {}
+            Some(_) => SocietyError::ProposalArchived(proposal_id).panic(),
+            None => SocietyError::ProposalNotFound.panic(),
+        }
+        let len_max = self.policy.comment_len_max;
+        require(
+            !text.is_empty() && text.len() as u64 <= len_max,
+            SocietyError::CommentLength(len_max),
+        );
+        let initial_storage = env::storage_usage();
+        let mut comment_list = self.comment_list(proposal_id);
+        let limit = self.policy.comment_limit;
+        require(
+            comment_list.len() < limit,
+            SocietyError::CommentLimit(limit),
+        );
+        if let Some(reply_to) = reply_to {
+            require(reply_to < comment_list.len(), SocietyError::CommentNotFound);
+        }
+        let comment_id = comment_list.len();
+        comment_list.push(&CommentState {
+            author: signer_account_id.clone(),
+            timestamp: env::block_timestamp(),
+            text,
+            reply_to,
+            hidden: false,
+        });
+        self.comment_list.insert(&proposal_id, &comment_list);
+        log_event(
+            "comment_added",
+            json!({
+                "proposal_id": proposal_id,
+                "comment_id": comment_id,
+                "author": signer_account_id,
+                "reply_to": reply_to,
+            }),
+        );
+        self.charge_storage(&signer_account_id, initial_storage);
+        comment_id
+    }
+
+    /// Member pays the dues for as many periods as the deposit covers, the rest is refunded,
+    /// a lapsed member starts a new period from now
+    #[payable]
+    pub fn pay_dues(&mut self) -> U64 {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let dues = match self.policy.dues.clone() {
+            Some(dues) => dues,
+            None => SocietyError::DuesDisabled.panic(),
+        };
+        let amount = env::attached_deposit();
+        require(
+            amount >= dues.amount.0,
+            SocietyError::DuesRequired(dues.amount.0),
+        );
+        let periods = amount / dues.amount.0;
+        let refund = amount - periods * dues.amount.0;
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(refund);
+        }
+        let start = if self.is_lapsed(&signer_account_id) {
+            env::block_timestamp()
+        } else {
+            self.paid_through(&signer_account_id)
+        };
+        let paid_through = u64::try_from(periods)
+            .ok()
+            .and_then(|periods| periods.checked_mul(dues.period.0))
+            .and_then(|duration| start.checked_add(duration))
+            .unwrap_or_else(|| SocietyError::DuesOverflow.panic());
+        let initial_storage = env::storage_usage();
+        self.dues_list.insert(&signer_account_id, &paid_through);
+        log_event(
+            "dues_paid",
+            json!({
+                "account_id": signer_account_id,
+                "amount": U128(amount - refund),
+                "paid_through": U64(paid_through),
+            }),
+        );
+        self.charge_storage(&signer_account_id, initial_storage);
+        paid_through.into()
+    }
+
+    /// Member edits the own profile, the storage is paid by the member
+    pub fn update_profile(
+        &mut self,
+        name: Option<String>,
+        avatar: Option<String>,
+        bio: Option<String>,
+        links: Option<Vec<String>>,
+    ) -> MemberProfile {
+        let signer_account_id = env::signer_account_id();
+        require(
+            self.is_member(signer_account_id.clone()),
+            SocietyError::OnlyMember,
+        );
+        let mut profile = self
+            .profile_list
+            .get(&signer_account_id)
+            .unwrap_or_default();
+        if let Some(name) = name {
+            if name.len() > PARAM_PROFILE_NAME_LEN_MAX {
+                SocietyError::FieldLength("profile name", PARAM_PROFILE_NAME_LEN_MAX).panic()
+            }
+            profile.name = name;
+        }
+        if let Some(avatar) = avatar {
+            if !avatar.is_empty() && !Attachment::is_cid(&avatar) {
+                SocietyError::InvalidField("profile avatar", "must be an IPFS CID".to_string())
+                    .panic()
+            }
+            profile.avatar = Some(avatar).filter(|avatar| !avatar.is_empty());
+        }
+        if let Some(bio) = bio {
+            if bio.len() > PARAM_PROFILE_BIO_LEN_MAX {
+                SocietyError::FieldLength("profile bio", PARAM_PROFILE_BIO_LEN_MAX).panic()
+            }
+            profile.bio = bio;
+        }
+        if let Some(links) = links {
+            if links.len() > PARAM_PROFILE_LINK_MAX {
+                SocietyError::InvalidField(
+                    "profile links",
+                    format!("must have less {} links", PARAM_PROFILE_LINK_MAX),
+                )
+                .panic()
+            }
+            for link in links.iter() {
+                if link.len() > PARAM_PROFILE_LINK_LEN_MAX || !link.starts_with("https://") {
+                    SocietyError::InvalidField(
+                        "profile links",
+                        format!(
+                            "must be https:// links up to {} bytes",
+                            PARAM_PROFILE_LINK_LEN_MAX
+                        ),
+                    )
+                    .panic()
+                }
+            }
+            profile.links = links;
+        }
+        let initial_storage = env::storage_usage();
+        self.profile_list.insert(&signer_account_id, &profile);
+        log_event(
+            "profile_updated",
+            json!({ "account_id": signer_account_id }),
+        );
+        self.charge_storage(&signer_account_id, initial_storage);
+        self.member_profile(signer_account_id)
+    }
+
+    fn comment_list(&self, proposal_id: u64) -> Vector<CommentState> {
+        match self.comment_list.get(&proposal_id) {
+            Some(comment_list) => comment_list,
+            None => Vector::new(StorageKey::ProposalComment {
+                hash: hash(proposal_id.to_string()),
+            }),
+        }
+    }
+
+    /// Comments of the proposal, the text of the hidden ones is left out
+    pub fn proposal_comments(
+        &self,
+        proposal_id: u64,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Comment> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.comment_list(proposal_id)
+            .iter()
+            .enumerate()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|(id, state)| Comment {
+                id: id as u64,
+                author: state.author,
+                timestamp: U64(state.timestamp),
+                text: if state.hidden { None } else { Some(state.text) },
+                reply_to: state.reply_to,
+                hidden: state.hidden,
+            })
+            .collect()
+    }
+
+    fn push_revision(&mut self, proposal_id: u64, proposal: &ProposalState) {
+        let mut revision_list = self.revision_list(proposal_id);
+        revision_list.push(&ProposalRevision {
+            timestamp: U64(env::block_timestamp()),
+            title: proposal.title.clone(),
+            description: proposal.description.clone(),
+            script: proposal.script.clone(),
+            attachments: proposal.attachments.clone(),
+        });
+        self.revision_list.insert(&proposal_id, &revision_list);
+    }
+
+    fn revision_list(&self, proposal_id: u64) -> Vector<ProposalRevision> {
+        match self.revision_list.get(&proposal_id) {
+            Some(revision_list) => revision_list,
+            None => Vector::new(StorageKey::ProposalRevision {
+                hash: hash(proposal_id.to_string()),
+            }),
+        }
+    }
+
+    /// Previous versions of the proposal from the oldest one
+    pub fn proposal_revisions(&self, proposal_id: u64) -> Vec<ProposalRevision> {
+        self.revision_list(proposal_id).to_vec()
+    }
+
+    /// The author can open a limited number of proposals of each kind
+    /// or update the given one while it is in the draft status
+    fn assert_proposal_open(
+        &self,
+        author: &AccountId,
+        kind: &ProposalKind,
+        proposal_id: Option<u64>,
+    ) {
+        if let Some(error) = self.proposal_open_error(author, kind, proposal_id) {
+            error.panic()
+        }
+    }
+
+    fn proposal_open_error(
+        &self,
+        author: &AccountId,
+        kind: &ProposalKind,
+        proposal_id: Option<u64>,
+    ) -> Option<SocietyError> {
+        let active_proposal_list = self.active_proposal_list(author);
+        match proposal_id {
+            Some(proposal_id) => {
+                let proposal = match self.proposal_list.get(proposal_id) {
+                    Some(proposal) if active_proposal_list.contains(&proposal_id) => proposal,
+                    _ => return Some(SocietyError::ProposalNotActive(proposal_id)),
+                };
+                if proposal.timestamp + PARAM_TIME_LOCK >= env::block_timestamp() {
+                    return Some(SocietyError::ProposalLocked(proposal_id));
+                }
+                if !proposal.is_draft() {
+                    return Some(SocietyError::ProposalNotDraft(proposal_id));
+                }
+                if &proposal.kind != kind {
+                    return Some(SocietyError::ProposalKindMismatch(proposal_id));
+                }
+            }
+            None => {
+                let limit = self.policy.proposal_limit(kind);
+                let open = active_proposal_list
+                    .iter()
+                    .filter_map(|proposal_id| self.proposal_list.get(proposal_id))
+                    .filter(|proposal| &proposal.kind == kind)
+                    .count() as u64;
+                if open >= limit {
+                    return Some(SocietyError::ProposalLimit(limit));
+                }
+            }
+        }
+        None
+    }
+
+    fn active_proposal_list(&self, account_id: &AccountId) -> UnorderedSet<u64> {
+        match self.active_proposal.get(account_id) {
+            Some(proposal_list) => proposal_list,
+            None => UnorderedSet::new(StorageKey::AuthorActive {
+                hash: hash(account_id.clone()),
+            }),
+        }
+    }
+
+    fn close_proposal(&mut self, author: &AccountId, proposal_id: u64) {
+        let mut active_proposal_list = self.active_proposal_list(author);
+        active_proposal_list.remove(&proposal_id);
+        if active_proposal_list.is_empty() {
+            self.active_proposal.remove(author);
+        } else {
+            self.active_proposal.insert(author, &active_proposal_list);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_proposal(
+        &mut self,
+        author: AccountId,
+        kind: ProposalKind,
+        proposal_id: Option<u64>,
+        title: Option<String>,
+        description: Option<String>,
+        script: Option<String>,
+        attachments: Option<Vec<Attachment>>,
+    ) -> u64 {
+        if let Some(attachments) = &attachments {
+            assert_attachments(attachments);
+        }
+        let mut initial_storage = env::storage_usage();
+        self.assert_proposal_open(&author, &kind, proposal_id);
+        let status = ProposalStatus::Vote;
+        let proposal_id = match proposal_id {
+            Some(proposal_id) => {
+                let proposal = match self.proposal_list.get(proposal_id) {
+                    Some(proposal) => proposal,
+                    None => SocietyError::ProposalNotFound.panic(),
+                };
+                if proposal.status == ProposalStatus::Vote {
+                    self.fund_proposal -= proposal.fund();
+                }
+                initial_storage -= self.clear_votes(proposal_id);
+                self.release_bond(&proposal, true);
+                self.push_revision(proposal_id, &proposal);
+                log_event(
+                    "proposal_updated",
+                    json!({
+                        "proposal_id": proposal_id,
+                        "author": author,
+                        "kind": kind,
+                    }),
+                );
+                let mut state =
+                    ProposalState::new(title, description, author.clone(), kind, status, script);
+                state.attachments = attachments.unwrap_or(proposal.attachments);
+                self.proposal_list.replace(proposal_id, &state);
+                proposal_id
+            }
+            None => {
+                let proposal_id = self.proposal_list.len();
+                let mut active_proposal_list = self.active_proposal_list(&author);
+                active_proposal_list.insert(&proposal_id);
+                self.active_proposal.insert(&author, &active_proposal_list);
+                let mut author_proposal_list = self.author_proposal_list(&author);
+                author_proposal_list.push(&proposal_id);
+                self.author_proposal_list
+                    .insert(&author, &author_proposal_list);
+                log_event(
+                    "proposal_created",
+                    json!({
+                        "proposal_id": proposal_id,
+                        "author": author,
+                        "kind": kind,
+                    }),
+                );
+                let mut state =
+                    ProposalState::new(title, description, author.clone(), kind, status, script);
+                state.attachments = attachments.unwrap_or_default();
+                self.proposal_list.push(&state);
+                proposal_id
+            }
+        };
+        self.charge_storage(&author, initial_storage);
+        proposal_id
+    }
+
+    pub fn stream_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Stream> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.stream_list
+            .iter()
+            .enumerate()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|(id, state)| Stream {
+                id: id as u64,
+                proposal_id: state.proposal_id,
+                claimable: U128(state.claimable()),
+                receiver: state.receiver,
+                total: U128(state.total),
+                claimed: U128(state.claimed),
+                start: U64(state.start),
+                end: U64(state.end),
+                cliff: U64(state.cliff),
+                canceled: state.canceled.map(U64),
+            })
+            .collect()
+    }
+
+    pub fn grant_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Grant> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.grant_list
+            .iter()
+            .enumerate()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|(id, state)| Grant {
+                id: id as u64,
+                proposal_id: state.proposal_id,
+                receiver: state.receiver,
+                milestones: state.milestones,
+            })
+            .collect()
+    }
+
+    pub fn bounty_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Bounty> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.bounty_list
+            .iter()
+            .enumerate()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|(id, state)| Bounty {
+                id: id as u64,
+                proposal_id: state.proposal_id,
+                reward: state.reward,
+                deadline: state.deadline,
+                claim: state.claim,
+                done: state.done,
+                canceled: state.canceled,
+            })
+            .collect()
+    }
+
+    pub fn acquisition_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Acquisition> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.acquisition_list
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn auction_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Auction> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.auction_list
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn exhibition_list(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<Exhibition> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.exhibition_list
+            .iter()
+            .enumerate()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|(id, state)| Exhibition {
+                id: id as u64,
+                proposal_id: state.proposal_id,
+                title: state.title,
+                description: state.description,
+                curator: state.curator,
+                items: state.items.len() as u64,
+                open: U64(state.open),
+                close: U64(state.close),
+            })
+            .collect()
+    }
+
+    pub fn exhibition_items(
+        &self,
+        exhibition_id: u64,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<ExhibitionItem> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.exhibition_state(exhibition_id)
+            .items
+            .into_iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    pub fn member_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<AccountId> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        require(
+            self.member_list.len() > start_index,
+            SocietyError::OutOfBounds,
+        );
+        self.member_list
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .collect()
+    }
+
+    pub fn member(&self, account_id: ValidAccountId) -> Option<MemberProfile> {
+        let account_id: AccountId = account_id.into();
+        if !self.is_member(account_id.clone()) {
+            return None;
+        }
+        Some(self.member_profile(account_id))
+    }
+
+    pub fn member_standing(&self, account_id: ValidAccountId) -> Option<MemberStanding> {
+        let account_id: AccountId = account_id.into();
+        if !self.is_member(account_id.clone()) {
+            return None;
+        }
+        Some(self.standing(account_id))
+    }
+
+    /// Dues standing of the members, `paid_through` is none while the dues are disabled
+    pub fn member_standings(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<MemberStanding> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.member_list
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|account_id| self.standing(account_id))
+            .collect()
+    }
+
+    fn standing(&self, account_id: AccountId) -> MemberStanding {
+        MemberStanding {
+            paid_through: self
+                .policy
+                .dues
+                .as_ref()
+                .map(|_| self.paid_through(&account_id).into()),
+            lapsed: self.is_lapsed(&account_id),
+            account_id,
+        }
+    }
+
+    /// `member_list` with the profiles
+    pub fn member_profiles(&self, offset: Option<u64>, limit: Option<u64>) -> Vec<MemberProfile> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.member_list
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|account_id| self.member_profile(account_id))
+            .collect()
+    }
+
+    pub fn proposal_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Proposal> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.proposal_list
+            .iter()
+            .enumerate()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .map(|(id, state)| state.into_proposal(id as u64))
+            .collect()
+    }
+
+    pub fn get_proposal(&self, proposal_id: u64) -> Option<Proposal> {
+        self.proposal_list
+            .get(proposal_id)
+            .map(|state| state.into_proposal(proposal_id))
+    }
+
+    pub fn active_proposal_of(&self, account_id: ValidAccountId) -> Vec<u64> {
+        self.active_proposal_list(account_id.as_ref()).to_vec()
+    }
+
+    pub fn proposals_by_author(
+        &self,
+        account_id: ValidAccountId,
+        offset: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Proposal> {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        self.author_proposal_list(account_id.as_ref())
+            .iter()
+            .skip(start_index as usize)
+            .take(limit as usize)
+            .filter_map(|proposal_id| self.get_proposal(proposal_id))
+            .collect()
+    }
+
+    /// Page of the matching proposals, the scan stops once the page is full unless
+    /// `count_total` asks for the number of all matches
+    pub fn proposals(
+        &self,
+        filter: Option<ProposalFilter>,
+        offset: Option<u64>,
+        limit: Option<u64>,
+        order: Option<ProposalOrder>,
+        count_total: Option<bool>,
+    ) -> ProposalPage {
+        let limit = limit.unwrap_or(100);
+        require(limit != 0, SocietyError::ZeroValue("limit"));
+        let start_index: u64 = offset.unwrap_or(0);
+        let filter = filter.unwrap_or_default();
+        let count_total = count_total.unwrap_or(false);
+        let id_list: Box<dyn DoubleEndedIterator<Item = u64>> = match filter.author.as_ref() {
+            Some(author) => Box::new(
+                self.author_proposal_list(author.as_ref())
+                    .to_vec()
+                    .into_iter(),
+            ),
+            None => Box::new(0..self.proposal_list.len()),
+        };
+        let id_list: Box<dyn Iterator<Item = u64>> = match order {
+            Some(ProposalOrder::Desc) => Box::new(id_list.rev()),
+            _ => Box::new(id_list),
+        };
+        let mut total = 0;
+        let mut proposals = vec![];
+        for id in id_list {
+            if !count_total && proposals.len() as u64 == limit {
+                break;
+            }
+            let state = match self.proposal_list.get(id) {
+                Some(state) => state,
+                None => continue,
+            };
+            if !filter.is_match(&state) {
+                continue;
+            }
+            if total >= start_index && (proposals.len() as u64) < limit {
+                proposals.push(state.into_proposal(id));
+            }
+            total += 1;
+        }
+        ProposalPage {
+            total: if count_total { Some(total) } else { None },
+            proposals,
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit {
+    use super::*;
+    use near_sdk::json_types::ValidAccountId;
+    use near_sdk::serde_json::Value;
+    use near_sdk::test_utils::accounts;
+    use near_sdk::test_utils::get_logs;
+    use near_sdk::test_utils::VMContextBuilder;
+    use near_sdk::testing_env;
+    use near_sdk::MockedBlockchain;
+
+    pub fn new_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
+        let mut builder = VMContextBuilder::new();
+        builder
+            .current_account_id(accounts(0))
+            .signer_account_id(predecessor_account_id.clone())
+            .predecessor_account_id(predecessor_account_id);
+        builder
+    }
+
+    pub fn event_log(event: &str, data: Value) -> String {
+        let event = json!({
+            "standard": "cryptoartdao",
+            "version": "1.0.0",
+            "event": event,
+            "data": [data],
+        });
+        format!("EVENT_JSON:{}", event)
+    }
+
+    pub fn new_contract() -> Society {
+        let mut contract = Society::init(vec![accounts(1)]);
+        deposit_storage(&mut contract, accounts(1));
+        contract
+    }
+
+    /// Same as a `storage_deposit` call for the account, without touching the context
+    pub fn deposit_storage(contract: &mut Society, account_id: ValidAccountId) {
+        let amount = storage_cost(PARAM_STORAGE_PROPOSAL * 10);
+        let mut storage = contract
+            .storage_list
+            .get(account_id.as_ref())
+            .unwrap_or_default();
+        storage.total += amount;
+        contract.storage_list.insert(account_id.as_ref(), &storage);
+        contract.storage_total += amount;
+    }
+
+    #[test]
+    fn consensus_cases() {
+        assert!(consensus(1, 1));
+        assert_eq!(!consensus(2, 1), consensus(2, 2));
+        assert_eq!(!consensus(3, 1), consensus(3, 2));
+        assert_eq!(!consensus(4, 2), consensus(4, 3));
+        assert_eq!(!consensus(5, 2), consensus(5, 3));
+        assert_eq!(!consensus(6, 3), consensus(6, 4));
+        assert_eq!(!consensus(7, 3), consensus(7, 4));
+        assert_eq!(!consensus(8, 4), consensus(8, 5));
+        assert_eq!(!consensus(9, 4), consensus(9, 5));
+        assert_eq!(!consensus(10, 5), consensus(10, 6));
+        assert_eq!(!consensus(11, 5), consensus(11, 6));
+        assert_eq!(!consensus(12, 6), consensus(12, 7));
+        assert_eq!(!consensus(13, 6), consensus(13, 7));
+    }
+
+    #[test]
+    fn balance() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = new_contract();
+        let balance = contract.balance();
+        assert_eq!(U128(86720400000000000000000000), balance.liquid);
+        assert_eq!(U128(0), balance.staked);
+        assert_eq!(U128(0), balance.unstaking);
+    }
+
+    #[test]
+    fn member_list() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = new_contract();
+        assert_eq!(
+            vec![accounts(1).into()] as Vec<AccountId>,
+            contract.member_list(None, None)
+        );
+    }
+
+    #[test]
+    fn update_profile() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        assert_eq!(
+            None,
+            contract.member(accounts(1)).unwrap().admitted_by_proposal
+        );
+        let storage = contract.storage_balance_of(accounts(1)).unwrap().available;
+        contract.update_profile(
+            Some("Alice".to_string()),
+            Some(format!("Qm{}", "a".repeat(44))),
+            Some("Painter".to_string()),
+            Some(vec!["https://alice.art".to_string()]),
+        );
+        contract.update_profile(None, None, Some("Sculptor".to_string()), None);
+        let profile = contract.member(accounts(1)).unwrap();
+        assert_eq!("Alice", profile.name);
+        assert_eq!("Sculptor", profile.bio);
+        assert_eq!(vec!["https://alice.art".to_string()], profile.links);
+        assert!(
+            contract
+                .storage_balance_of(accounts(1))
+                .unwrap()
+                .available
+                .0
+                < storage.0
+        );
+        assert!(contract.member(accounts(2)).is_none());
+        contract.add_member(accounts(2).into());
+        let profiles = contract.member_profiles(None, None);
+        assert_eq!(2, profiles.len());
+        assert_eq!("", profiles[1].name);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ONLY_MEMBER")]
+    fn update_profile_not_member() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.update_profile(Some("Bob".to_string()), None, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_FIELD: Field profile links")]
+    fn update_profile_invalid_link() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.update_profile(None, None, None, Some(vec!["javascript:".to_string()]));
+    }
+
+    #[test]
+    fn member_admitted_by_proposal() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member_proposal(None, None, None, None);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(0);
+        let profile = contract.member(accounts(2)).unwrap();
+        assert_eq!(Some(0), profile.admitted_by_proposal);
+    }
+
+    #[test]
+    fn pay_dues() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        enable_dues(&mut contract, 100, 1_000);
+        assert_eq!(0, contract.dues_start);
+        contract.add_member(accounts(2).into());
+        testing_env!(context.attached_deposit(250).build());
+        assert_eq!(U64(2_000), contract.pay_dues());
+        let standing = contract.member_standing(accounts(1)).unwrap();
+        assert_eq!(Some(U64(2_000)), standing.paid_through);
+        assert!(!standing.lapsed);
+        testing_env!(context.block_timestamp(1_000).attached_deposit(0).build());
+        assert!(contract.member_standing(accounts(2)).unwrap().lapsed);
+        assert_eq!(1, contract.vote_total());
+        testing_env!(context.block_timestamp(2_600).build());
+        assert_eq!(0, contract.vote_total());
+        testing_env!(context.attached_deposit(100).build());
+        assert_eq!(U64(3_600), contract.pay_dues());
+        assert_eq!(1, contract.vote_total());
+        assert_eq!(2, contract.member_standings(None, None).len());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DUES_LAPSED")]
+    fn vote_lapsed() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        enable_dues(&mut contract, 100, 1_000);
+        testing_env!(context.block_timestamp(1_600).build());
+        let proposal_id = contract.add_policy_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+    }
+
+    #[test]
+    fn vote_total_lapsed() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        testing_env!(context.block_timestamp(100).build());
+        enable_dues(&mut contract, 100, 1_000);
+        assert_eq!(100, contract.dues_start);
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let proposal_id = contract.add_policy_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({}).to_string(),
+            None,
+            None,
+        );
+        testing_env!(context.block_timestamp(1_700).attached_deposit(100).build());
+        contract.pay_dues();
+        assert_eq!(1, contract.vote_total());
+        contract.vote_approve(proposal_id);
+        assert!(contract.get_proposal(proposal_id).unwrap().status == ProposalStatus::Accepted);
+    }
+
+    #[test]
+    fn vote_total_new_member() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_fund_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"fund": "1"}).to_string(),
+            None,
+            None,
+        );
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        testing_env!(context.attached_deposit(0).build());
+        contract.vote_approve(proposal_id);
+        assert!(contract.get_proposal(proposal_id).unwrap().status == ProposalStatus::Vote);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(proposal_id);
+        assert!(contract.get_proposal(proposal_id).unwrap().status == ProposalStatus::Accepted);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DUES_OVERFLOW")]
+    fn pay_dues_overflow() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        enable_dues(&mut contract, 1, PARAM_DUES_PERIOD_MAX);
+        testing_env!(context.attached_deposit(1_000).build());
+        contract.pay_dues();
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DUES_PERIOD: Dues period and grace cannot exceed")]
+    fn dues_period_max() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        enable_dues(&mut contract, 100, PARAM_DUES_PERIOD_MAX + 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DUES_PERIOD: Dues period and grace cannot exceed")]
+    fn dues_grace_max() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.add_policy_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"dues": {"amount": "100", "period": "1000", "grace": U64(u64::MAX)}})
+                .to_string(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn dues_grace_saturating() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        // a policy stored before the bound still must not break the voting
+        contract.policy.dues = Some(DuesPolicy {
+            amount: U128(100),
+            period: U64(1_000),
+            grace: U64(u64::MAX),
+        });
+        testing_env!(context.block_timestamp(u64::MAX).build());
+        assert!(!contract.member_standing(accounts(1)).unwrap().lapsed);
+        assert_eq!(1, contract.vote_total());
+    }
+
+    fn enable_dues(contract: &mut Society, amount: u128, period: u64) {
+        let proposal_id = contract.add_policy_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"dues": {"amount": U128(amount), "period": U64(period), "grace": U64(500)}})
+                .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert!(contract.policy.dues.is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_DUES_DISABLED")]
+    fn pay_dues_disabled() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(100).build());
+        let mut contract = new_contract();
+        contract.pay_dues();
+    }
+
+    #[test]
+    fn is_member() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = new_contract();
+        assert!(contract.is_member(accounts(1).into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_SCRIPT")]
+    fn add_fund_proposal_invalid_script() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": 1}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_FIELD_LENGTH: Field title must be less 170 length")]
+    fn add_fund_proposal_long_title() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".repeat(171), "b".to_string(), script, None, None);
+    }
+
+    #[test]
+    fn add_fund_proposal() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_fund_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            "{\"fund\": \"1000000000000000000000000\"}".to_string(),
+            None,
+            None,
+        );
+        assert_eq!(0, proposal_id);
+        assert!(get_logs().contains(&event_log(
+            "proposal_created",
+            json!({"proposal_id": 0, "author": accounts(1), "kind": "FundRequest"}),
+        )));
+        testing_env!(context.attached_deposit(0).build());
+        contract.vote_approve(proposal_id);
+        assert_eq!(
+            vec![
+                event_log(
+                    "vote_cast",
+                    json!({"proposal_id": 0, "account_id": accounts(1), "vote": "approve"}),
+                ),
+                event_log(
+                    "proposal_status_changed",
+                    json!({"proposal_id": 0, "status": "Accepted"}),
+                ),
+                event_log(
+                    "payout",
+                    json!({
+                        "receiver_id": accounts(1),
+                        "amount": U128(PARAM_PROPOSAL_BOND),
+                        "source": "proposal_bond",
+                    }),
+                ),
+                event_log(
+                    "payout",
+                    json!({
+                        "receiver_id": accounts(1),
+                        "amount": "1000000000000000000000000",
+                        "source": "fund_request",
+                    }),
+                ),
+            ],
+            get_logs()
+        );
+    }
+
+    #[test]
+    fn add_function_call_proposal() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_function_call_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "receiver_id": accounts(3),
+                "actions": [{
+                    "method_name": "nft_buy",
+                    "args": "e30=",
+                    "deposit": "1000000000000000000000000",
+                    "gas": "50000000000000",
+                }],
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        assert_eq!(0, proposal_id);
+        assert_eq!(1000000000000000000000000, contract.fund_proposal);
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        let proposal = &contract.proposal_list(None, None)[0];
+        assert!(proposal.status == ProposalStatus::Accepted);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ACTION_REQUIRED")]
+    fn add_function_call_proposal_without_actions() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.add_function_call_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "receiver_id": accounts(3),
+                "actions": [],
+            })
+            .to_string(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn add_stake_proposal() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_stake_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"pool_id": accounts(4), "amount": "5000000000000000000000000"}).to_string(),
+            None,
+            None,
+        );
+        assert_eq!(5000000000000000000000000, contract.fund_proposal);
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])]
+        );
+        contract.on_staking_pool(
+            accounts(4).into(),
+            ProposalKind::StakeRequest,
+            U128(5000000000000000000000000),
+        );
+        assert_eq!(U128(5000000000000000000000000), contract.balance().staked);
+    }
+
+    #[test]
+    fn unstake_proposal_concurrent() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        contract.stake_list.insert(
+            &accounts(4).into(),
+            &StakeState {
+                staked: 5,
+                unstaking: 0,
+            },
+        );
+        let script = json!({"pool_id": accounts(4), "amount": "5"}).to_string();
+        let first = contract.add_unstake_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script.clone(),
+            None,
+            None,
+        );
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        let second =
+            contract.add_unstake_proposal("a".to_string(), "b".to_string(), script, None, None);
+        contract.vote_approve(first);
+        contract.vote_approve(second);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(first);
+        assert_eq!(U128(0), contract.balance().staked);
+        assert_eq!(U128(5), contract.balance().unstaking);
+        contract.vote_approve(second);
+        assert!(contract.get_proposal(second).unwrap().status == ProposalStatus::Accepted);
+        assert_eq!(U128(5), contract.balance().unstaking);
+        assert!(get_logs().contains(&event_log(
+            "proposal_execution_skipped",
+            json!({
+                "proposal_id": second,
+                "error": "ERR_NOT_ENOUGH_STAKED",
+                "message": SocietyError::NotEnoughStaked.to_string(),
+            }),
+        )));
+    }
+
+    #[test]
+    fn on_staking_pool_failed() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.stake_list.insert(
+            &accounts(4).into(),
+            &StakeState {
+                staked: 0,
+                unstaking: 5,
+            },
+        );
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        contract.on_staking_pool(accounts(4).into(), ProposalKind::UnstakeRequest, U128(5));
+        assert_eq!(U128(5), contract.balance().staked);
+        assert_eq!(U128(0), contract.balance().unstaking);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_ENOUGH_STAKED")]
+    fn add_unstake_proposal_over_staked() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.add_unstake_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"pool_id": accounts(4), "amount": "1"}).to_string(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn claim_stream() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_stream_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"total": "1000", "start": "100", "end": "200", "cliff": "150"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(1000, contract.fund_proposal);
+        testing_env!(context.block_timestamp(120).build());
+        assert_eq!(0, contract.stream_state(0).claimable());
+        testing_env!(context.block_timestamp(160).build());
+        assert_eq!(U128(600), contract.claim_stream(0));
+        assert_eq!(400, contract.fund_proposal);
+        assert!(get_logs().contains(&event_log(
+            "payout",
+            json!({"receiver_id": accounts(1), "amount": "600", "source": "stream"}),
+        )));
+        let proposal_id = contract.add_stream_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"stream_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        assert_eq!(1, proposal_id);
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        assert_eq!(Some(160), contract.stream_state(0).canceled);
+        testing_env!(context.block_timestamp(200).build());
+        assert_eq!(0, contract.stream_state(0).claimable());
+    }
+
+    #[test]
+    fn claim_stream_large() {
+        let mut context = new_context(accounts(1));
+        let total = 1000 * 10u128.pow(24);
+        testing_env!(context
+            .block_timestamp(0)
+            .account_balance(2 * total)
+            .build());
+        let mut contract = new_contract();
+        let year = 365 * 24 * 60 * 60 * 1_000_000_000u64;
+        let proposal_id = contract.add_stream_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"total": U128(total), "start": "0", "end": U64(year)}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context.block_timestamp(year / 2).build());
+        assert_eq!(U128(total / 2), contract.claim_stream(0));
+        testing_env!(context.block_timestamp(year / 3 * 2).build());
+        assert_eq!(
+            total / 3 * 2 - total / 2,
+            contract.stream_state(0).claimable()
+        );
+        let proposal_id = contract.add_stream_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"stream_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(total / 3 * 2 - total / 2, contract.fund_proposal);
+    }
+
+    #[test]
+    fn cancel_stream_twice() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        contract.add_stream_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"total": "1000", "start": "0", "end": "1000"}).to_string(),
+            None,
+            None,
+        );
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(0);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(0);
+        testing_env!(context.block_timestamp(500).build());
+        let script = json!({"stream_id": "0"}).to_string();
+        let first = contract.add_stream_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script.clone(),
+            None,
+            None,
+        );
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        let second = contract.add_stream_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script,
+            None,
+            None,
+        );
+        contract.vote_approve(first);
+        contract.vote_approve(second);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(first);
+        assert_eq!(500, contract.fund_proposal);
+        contract.vote_approve(second);
+        assert!(contract.get_proposal(second).unwrap().status == ProposalStatus::Accepted);
+        assert_eq!(500, contract.fund_proposal);
+        assert!(get_logs().contains(&event_log(
+            "proposal_execution_skipped",
+            json!({
+                "proposal_id": second,
+                "error": "ERR_STREAM_CANCELED",
+                "message": SocietyError::StreamCanceled.to_string(),
+            }),
+        )));
+    }
+
+    #[test]
+    fn cancel_stream() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        contract.add_stream_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"total": "1000", "start": "0", "end": "1000"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(0);
+        testing_env!(context.block_timestamp(500).build());
+        let proposal_id = contract.add_stream_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"stream_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(500, contract.fund_proposal);
+        testing_env!(context.block_timestamp(900).build());
+        assert_eq!(U128(500), contract.claim_stream(0));
+        assert_eq!(0, contract.fund_proposal);
+        assert!(get_logs().contains(&event_log(
+            "payout",
+            json!({"receiver_id": accounts(1), "amount": "500", "source": "stream"}),
+        )));
+    }
+
+    #[test]
+    fn milestone_grant() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_milestone_grant_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"milestones": [
+                {"title": "sketch", "amount": "100"},
+                {"title": "final", "amount": "300"},
+            ]})
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(400, contract.fund_proposal);
+        contract.submit_milestone(0, 0, "ipfs://sketch".to_string());
+        contract.vote_milestone(0, 0, true);
+        assert_eq!(300, contract.fund_proposal);
+        let proposal_id = contract.add_grant_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"grant_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        let grant = &contract.grant_list(None, None)[0];
+        assert!(grant.milestones[0].status == MilestoneStatus::Paid);
+        assert!(grant.milestones[1].status == MilestoneStatus::Canceled);
+    }
+
+    #[test]
+    fn milestone_vote_storage() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let proposal_id = contract.add_milestone_grant_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"milestones": [{"title": "final", "amount": "300"}]}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(proposal_id);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.submit_milestone(0, 0, "ipfs://final".to_string());
+        let available = contract.storage_balance_of(accounts(2)).unwrap().available;
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_milestone(0, 0, true);
+        let voted = contract.storage_balance_of(accounts(2)).unwrap().available;
+        assert!(voted.0 < available.0);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_milestone(0, 0, true);
+        assert_eq!(
+            available,
+            contract.storage_balance_of(accounts(2)).unwrap().available
+        );
+    }
+
+    #[test]
+    fn cancel_grant_twice() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        contract.fund_proposal = 1000;
+        contract.add_milestone_grant_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"milestones": [{"title": "final", "amount": "300"}]}).to_string(),
+            None,
+            None,
+        );
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(0);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(0);
+        assert_eq!(1300, contract.fund_proposal);
+        let script = json!({"grant_id": "0"}).to_string();
+        let first = contract.add_grant_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script.clone(),
+            None,
+            None,
+        );
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        let second = contract.add_grant_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script,
+            None,
+            None,
+        );
+        contract.vote_approve(first);
+        contract.vote_approve(second);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(first);
+        contract.vote_approve(second);
+        assert_eq!(1000, contract.fund_proposal);
+    }
+
+    #[test]
+    fn vote_milestone_after_reject() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_milestone_grant_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"milestones": [{"title": "final", "amount": "300"}]}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        contract.submit_milestone(0, 0, "ipfs://draft".to_string());
+        contract.vote_milestone(0, 0, false);
+        contract.submit_milestone(0, 0, "ipfs://final".to_string());
+        contract.vote_milestone(0, 0, true);
+        let grant = &contract.grant_list(None, None)[0];
+        assert!(grant.milestones[0].status == MilestoneStatus::Paid);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_MILESTONE_NOT_REVIEW")]
+    fn vote_milestone_without_deliverable() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_milestone_grant_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"milestones": [{"title": "final", "amount": "300"}]}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        contract.vote_milestone(0, 0, true);
+    }
+
+    #[test]
+    fn bounty() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        let proposal_id = contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "100"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .build());
+        contract.claim_bounty(0);
+        assert_eq!(1000 + PARAM_BOUNTY_BOND, contract.fund_proposal);
+        contract.submit_bounty_work(0, "ipfs://work".to_string());
+        let proposal_id = contract.add_bounty_done_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"bounty_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        assert!(contract.bounty_list(None, None)[0].done);
+    }
+
+    #[test]
+    fn cancel_bounty() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        let proposal_id = contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "100"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .build());
+        contract.claim_bounty(0);
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .block_timestamp(200)
+            .build());
+        let proposal_id = contract.add_bounty_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"bounty_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        let bounty = &contract.bounty_list(None, None)[0];
+        assert!(bounty.done && bounty.canceled && bounty.claim.is_none());
+    }
+
+    #[test]
+    fn cancel_bounty_claimed_in_vote() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(3));
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "18446744073709551615"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(0);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(0);
+        let proposal_id = contract.add_bounty_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"bounty_id": "0"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .block_timestamp(10)
+            .build());
+        contract.claim_bounty(0);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .predecessor_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(proposal_id);
+        assert!(contract.get_proposal(proposal_id).unwrap().status == ProposalStatus::Accepted);
+        assert_eq!(1000 + PARAM_BOUNTY_BOND, contract.fund_proposal);
+        assert!(get_logs().contains(&event_log(
+            "proposal_execution_skipped",
+            json!({
+                "proposal_id": proposal_id,
+                "error": "ERR_BOUNTY_CLAIMED",
+                "message": SocietyError::BountyClaimed.to_string(),
+            }),
+        )));
+        let bounty = &contract.bounty_list(None, None)[0];
+        assert!(!bounty.done && bounty.claim.is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_BOUNTY_CLAIMED")]
+    fn cancel_bounty_claimed() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "18446744073709551615"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(0);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .block_timestamp(10)
+            .build());
+        contract.claim_bounty(0);
+        contract.add_bounty_cancel_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"bounty_id": "0"}).to_string(),
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn expire_bounty_claim() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        let proposal_id = contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "100"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .build());
+        contract.claim_bounty(0);
+        testing_env!(context.block_timestamp(101).attached_deposit(0).build());
+        contract.expire_bounty_claim(0);
+        assert_eq!(1000, contract.fund_proposal);
+        assert!(contract.bounty_list(None, None)[0].claim.is_none());
+    }
+
+    #[test]
+    fn bounty_claim_storage() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        let proposal_id = contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "100"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        let available = contract.storage_balance_of(accounts(2)).unwrap().available;
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .build());
+        contract.claim_bounty(0);
+        let claimed = contract.storage_balance_of(accounts(2)).unwrap().available;
+        assert!(claimed.0 < available.0);
+        testing_env!(context.attached_deposit(0).build());
+        contract.submit_bounty_work(0, "a".repeat(1000));
+        let submitted = contract.storage_balance_of(accounts(2)).unwrap().available;
+        assert!(claimed.0 - submitted.0 >= storage_cost(1000));
+        testing_env!(context
+            .predecessor_account_id(accounts(1))
+            .block_timestamp(101)
+            .build());
+        contract.expire_bounty_claim(0);
+        assert_eq!(
+            available,
+            contract.storage_balance_of(accounts(2)).unwrap().available
+        );
+    }
+
+    #[test]
+    fn expire_bounty_claim_refund() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.policy.bounty_forfeit = false;
+        let proposal_id = contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "100"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(PARAM_BOUNTY_BOND)
+            .build());
+        contract.claim_bounty(0);
+        testing_env!(context.block_timestamp(101).attached_deposit(0).build());
+        contract.expire_bounty_claim(0);
+        assert!(get_logs().contains(&event_log(
+            "payout",
+            json!({
+                "receiver_id": accounts(2),
+                "amount": U128(PARAM_BOUNTY_BOND),
+                "source": "bounty_bond",
+            }),
+        )));
+        assert_eq!(1000, contract.fund_proposal);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_BOUNTY_CLAIMED")]
+    fn claim_bounty_twice() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_bounty_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"reward": "1000", "deadline": "100"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context.attached_deposit(PARAM_BOUNTY_BOND).build());
+        contract.claim_bounty(0);
+        contract.claim_bounty(0);
+    }
+
+    #[test]
+    fn member_token() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = new_contract();
+        let token = contract.nft_token("member:bob".to_string()).unwrap();
+        assert_eq!(AccountId::from("bob"), token.owner_id);
+        assert_eq!(
+            1,
+            contract.nft_tokens_for_owner(accounts(1), None, None).len()
+        );
+        assert_eq!(U128(0), contract.nft_supply_for_owner(accounts(2)));
+        assert_eq!("nft-1.0.0", contract.nft_metadata().spec);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_TOKEN_NOT_TRANSFERABLE")]
+    fn member_token_transfer() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(1).build());
+        let mut contract = new_contract();
+        contract.nft_transfer(accounts(2), "member:bob".to_string(), None, None);
+    }
+
+    #[test]
+    fn art_mint() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_art_mint_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "title": "Sunrise",
+                "media": "ipfs://bafy",
+                "media_hash": near_sdk::base64::encode(env::sha256(b"sunrise")),
+                "copies": 2,
+                "receiver_id": accounts(1),
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(U128(3), contract.nft_total_supply());
+        assert_eq!(3, contract.nft_tokens(None, None).len());
+        assert_eq!(U128(3), contract.nft_supply_for_owner(accounts(1)));
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_transfer(accounts(2), "1".to_string(), None, None);
+        let token = contract.nft_token("1".to_string()).unwrap();
+        assert_eq!(AccountId::from(accounts(2)), token.owner_id);
+        assert_eq!(Some(2), token.metadata.unwrap().copies);
+    }
+
+    #[test]
+    fn art_payout() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_art_mint_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "title": "Sunrise",
+                "media": "ipfs://bafy",
+                "media_hash": near_sdk::base64::encode(env::sha256(b"sunrise")),
+                "receiver_id": accounts(1),
+                "royalty": {accounts(2).to_string(): 1000, accounts(0).to_string(): 500},
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        let payout = contract.nft_payout("0".to_string(), U128(1000), 3).payout;
+        assert_eq!(U128(100), payout[&AccountId::from(accounts(2))]);
+        assert_eq!(U128(50), payout[&AccountId::from(accounts(0))]);
+        assert_eq!(U128(850), payout[&AccountId::from(accounts(1))]);
+        testing_env!(context
+            .attached_deposit(env::storage_byte_cost() * 100)
+            .build());
+        contract.nft_approve("0".to_string(), accounts(4), None);
+        assert!(contract.nft_is_approved("0".to_string(), accounts(4), Some(0)));
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        contract.nft_transfer_payout(accounts(3), "0".to_string(), Some(0), U128(1000), 3);
+        assert_eq!(U128(0), contract.balance().royalty);
+        let token = contract.nft_token("0".to_string()).unwrap();
+        assert_eq!(AccountId::from(accounts(3)), token.owner_id);
+        assert!(token.approved_account_ids.is_empty());
+        let payout = contract.nft_payout("0".to_string(), U128(1000), 3).payout;
+        assert_eq!(U128(850), payout[&AccountId::from(accounts(3))]);
+        testing_env!(context.attached_deposit(50).build());
+        contract.pay_royalty("0".to_string());
+        assert_eq!(U128(50), contract.balance().royalty);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_NOT_APPROVED")]
+    fn nft_transfer_approval_outdated() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.mint_token(
+            "0".to_string(),
+            accounts(1).into(),
+            TokenMetadata::default(),
+            HashMap::new(),
+        );
+        testing_env!(context
+            .attached_deposit(env::storage_byte_cost() * 100)
+            .build());
+        contract.nft_approve("0".to_string(), accounts(4), None);
+        contract.nft_approve("0".to_string(), accounts(4), None);
+        assert!(contract.nft_is_approved("0".to_string(), accounts(4), Some(1)));
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_revoke("0".to_string(), accounts(2));
+        testing_env!(context
+            .predecessor_account_id(accounts(4))
+            .attached_deposit(1)
+            .build());
+        contract.nft_transfer(accounts(3), "0".to_string(), Some(0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_ONLY_TOKEN_OWNER")]
+    fn nft_transfer_approval_revoked() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.mint_token(
+            "0".to_string(),
+            accounts(1).into(),
+            TokenMetadata::default(),
+            HashMap::new(),
+        );
+        testing_env!(context
+            .attached_deposit(env::storage_byte_cost() * 100)
+            .build());
+        contract.nft_approve("0".to_string(), accounts(4), None);
+        testing_env!(context.attached_deposit(1).build());
+        contract.nft_revoke_all("0".to_string());
+        assert!(!contract.nft_is_approved("0".to_string(), accounts(4), None));
+        testing_env!(context.predecessor_account_id(accounts(4)).build());
+        contract.nft_transfer(accounts(3), "0".to_string(), None, None);
+    }
+
+    #[test]
+    fn acquire_nft() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.policy.market_id = Some(accounts(4).into());
+        let proposal_id = contract.add_acquire_nft_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"nft_contract_id": accounts(3), "token_id": "7", "price": "1000"}).to_string(),
+            None,
+            None,
+        );
+        assert_eq!(1000, contract.fund_proposal);
+        contract.vote_approve(proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        let keep = contract.nft_on_transfer(
+            accounts(4).into(),
+            accounts(2).into(),
+            "7".to_string(),
+            "".to_string(),
+        );
+        assert!(matches!(keep, PromiseOrValue::Value(false)));
+        let acquisition = &contract.acquisition_list(None, None)[0];
+        assert!(acquisition.status == AcquisitionStatus::Acquired);
+    }
+
+    #[test]
+    fn acquire_nft_market_removed() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.policy.market_id = Some(accounts(4).into());
+        let proposal_id = contract.add_acquire_nft_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"nft_contract_id": accounts(3), "token_id": "7", "price": "1000"}).to_string(),
+            None,
+            None,
+        );
+        contract.policy.market_id = None;
+        contract.vote_approve(proposal_id);
+        assert!(contract.get_proposal(proposal_id).unwrap().status == ProposalStatus::Accepted);
+        assert_eq!(0, contract.fund_proposal);
+        assert!(get_logs().contains(&event_log(
+            "proposal_execution_skipped",
+            json!({
+                "proposal_id": proposal_id,
+                "error": "ERR_MARKET_NOT_CONFIGURED",
+                "message": SocietyError::MarketNotConfigured.to_string(),
+            }),
+        )));
+        assert!(contract.acquisition_list(None, None).is_empty());
+    }
+
+    #[test]
+    fn acquire_nft_failed() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.policy.market_id = Some(accounts(4).into());
+        let proposal_id = contract.add_acquire_nft_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"nft_contract_id": accounts(3), "token_id": "7", "price": "1000"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Failed]
+        );
+        contract.on_acquire_nft(0);
+        let acquisition = &contract.acquisition_list(None, None)[0];
+        assert!(acquisition.status == AcquisitionStatus::Failed);
+    }
+
+    #[test]
+    fn expire_acquisition() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        contract.policy.market_id = Some(accounts(4).into());
+        let script =
+            json!({"nft_contract_id": accounts(3), "token_id": "7", "price": "1000"}).to_string();
+        let proposal_id = contract.add_acquire_nft_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script.clone(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context.block_timestamp(PARAM_ACQUISITION_TIMEOUT).build());
+        contract.expire_acquisition(0);
+        assert_eq!(0, contract.fund_proposal);
+        testing_env!(context.predecessor_account_id(accounts(3)).build());
+        let keep = contract.nft_on_transfer(
+            accounts(4).into(),
+            accounts(2).into(),
+            "7".to_string(),
+            "".to_string(),
+        );
+        assert!(matches!(keep, PromiseOrValue::Value(true)));
+        testing_env!(context.predecessor_account_id(accounts(1)).build());
+        contract.add_acquire_nft_proposal("a".to_string(), "b".to_string(), script, None, None);
+        assert_eq!(1000, contract.fund_proposal);
     }
 
-    fn setup(&mut self, initial_members: Vec<ValidAccountId>) {
-        for member in initial_members {
-            self.add_member(member.into());
-        }
+    #[test]
+    #[should_panic(expected = "ERR_ACQUISITION_PENDING")]
+    fn expire_acquisition_early() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        contract.policy.market_id = Some(accounts(4).into());
+        let proposal_id = contract.add_acquire_nft_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"nft_contract_id": accounts(3), "token_id": "7", "price": "1000"}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .block_timestamp(PARAM_ACQUISITION_TIMEOUT - 1)
+            .build());
+        contract.expire_acquisition(0);
     }
 
-    fn account_locked_for_storage(&self) -> u128 {
-        env::storage_byte_cost() * Balance::from(env::storage_usage())
+    #[test]
+    fn auction_artwork() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_art_mint_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "title": "Sunrise",
+                "media": "ipfs://bafy",
+                "media_hash": near_sdk::base64::encode(env::sha256(b"sunrise")),
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        let proposal_id = contract.add_auction_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "token_id": "0",
+                "reserve_price": "100",
+                "min_increment": "10",
+                "duration": "1000",
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context
+            .predecessor_account_id(accounts(2))
+            .attached_deposit(100)
+            .build());
+        contract.bid(0);
+        testing_env!(context
+            .predecessor_account_id(accounts(3))
+            .attached_deposit(110)
+            .build());
+        contract.bid(0);
+        assert!(get_logs().contains(&event_log(
+            "payout",
+            json!({
+                "receiver_id": accounts(2),
+                "amount": U128(100),
+                "source": "auction_refund",
+            }),
+        )));
+        assert_eq!(110, contract.fund_proposal);
+        testing_env!(context.block_timestamp(1001).attached_deposit(0).build());
+        contract.settle_auction(0);
+        assert_eq!(0, contract.fund_proposal);
+        let token = contract.nft_token("0".to_string()).unwrap();
+        assert_eq!(AccountId::from(accounts(3)), token.owner_id);
     }
 
-    fn fund(&self) -> Balance {
-        env::account_balance()
-            - env::account_locked_balance()
-            - self.account_locked_for_storage()
-            - PARAM_FUND_RESERVE
-            - self.fund_proposal
+    #[test]
+    #[should_panic(expected = "ERR_AUCTION_DURATION")]
+    fn add_auction_proposal_long() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        contract.add_auction_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "nft_contract_id": accounts(3),
+                "token_id": "7",
+                "reserve_price": "100",
+                "min_increment": "10",
+                "duration": U64(u64::MAX),
+            })
+            .to_string(),
+            None,
+            None,
+        );
     }
 
-    pub fn balance(&self) -> U128 {
-        U128(self.fund())
+    #[test]
+    #[should_panic(expected = "ERR_BID_TOO_LOW: Must attach minimum 110 yoctoNEAR to bid")]
+    fn bid_below_increment() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.block_timestamp(0).build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_auction_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "nft_contract_id": accounts(3),
+                "token_id": "7",
+                "reserve_price": "100",
+                "min_increment": "10",
+                "duration": "1000",
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context.attached_deposit(100).build());
+        contract.bid(0);
+        testing_env!(context.attached_deposit(105).build());
+        contract.bid(0);
     }
 
-    pub fn is_member(&self, account_id: AccountId) -> bool {
-        self.member_list.contains(&account_id)
+    #[test]
+    fn exhibition() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_exhibition_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "title": "Dawn",
+                "description": "Morning works",
+                "curator": accounts(2),
+                "items": [
+                    {"nft_contract_id": accounts(3), "token_id": "1"},
+                    {"nft_contract_id": accounts(3), "token_id": "2"},
+                ],
+                "open": "100",
+                "close": "200",
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(2, contract.exhibition_list(None, None)[0].items);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.reorder_exhibition(0, vec![1, 0]);
+        let items = contract.exhibition_items(0, None, None);
+        assert_eq!("2", items[0].token_id);
+        assert_eq!("1", items[1].token_id);
     }
 
-    fn add_member(&mut self, account_id: AccountId) -> bool {
-        self.assert_is_member(account_id.clone());
-        self.member_list.insert(&account_id)
+    #[test]
+    #[should_panic(expected = "ERR_EXHIBITION_ORDER")]
+    fn reorder_exhibition_duplicate() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_exhibition_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({
+                "title": "Dawn",
+                "description": "Morning works",
+                "curator": accounts(2),
+                "items": [
+                    {"nft_contract_id": accounts(3), "token_id": "1"},
+                    {"nft_contract_id": accounts(3), "token_id": "2"},
+                ],
+                "open": "100",
+                "close": "200",
+            })
+            .to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        testing_env!(context.predecessor_account_id(accounts(2)).build());
+        contract.reorder_exhibition(0, vec![1, 1]);
     }
 
-    fn vote_total(&self) -> u64 {
-        self.member_list.len()
+    #[test]
+    fn add_member_proposal_nft_gated() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        deposit_storage(&mut contract, accounts(3));
+        contract.policy.member_nft_contract_id = Some(accounts(3).into());
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        assert!(matches!(
+            contract.add_member_proposal(None, None, None, None),
+            PromiseOrValue::Promise(_)
+        ));
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(
+                json!([{"token_id": "42", "owner_id": accounts(2)}])
+                    .to_string()
+                    .into_bytes()
+            )]
+        );
+        let proposal_id = contract.on_member_nft(MemberApplication {
+            account_id: accounts(2).into(),
+            title: None,
+            description: None,
+            bond: U128(PARAM_PROPOSAL_BOND),
+            proposal_id: None,
+            attachments: None,
+        });
+        let proposal = &contract.proposal_list(None, None)[proposal_id.unwrap() as usize];
+        assert_eq!(
+            Some(json!({"nft_contract_id": accounts(3), "token_id": "42"}).to_string()),
+            proposal.script
+        );
+        assert_eq!(U128(PARAM_PROPOSAL_BOND), proposal.bond);
     }
 
-    pub fn can_vote(self, proposal_id: u64, account_id: AccountId) -> bool {
-        match self.vote_list.get(&proposal_id) {
-            Some(vote_list) => !vote_list.contains(&account_id),
-            None => true,
-        }
+    #[test]
+    #[should_panic(expected = "ERR_STORAGE_DEPOSIT")]
+    fn add_member_proposal_without_storage() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        testing_env!(context
+            .signer_account_id(ValidAccountId::try_from("a".repeat(64)).unwrap())
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(None, None, None, None);
     }
 
-    pub fn vote_approve(&mut self, proposal_id: u64) {
-        self.vote(proposal_id, true)
+    #[test]
+    fn storage_deposit() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let account_id = ValidAccountId::try_from("a".repeat(64)).unwrap();
+        assert!(contract.storage_balance_of(account_id.clone()).is_none());
+        let min = contract.storage_balance_bounds().min.0;
+        testing_env!(context
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(min + 1000)
+            .build());
+        let storage = contract.storage_deposit(None, Some(true));
+        assert_eq!(U128(min), storage.total);
+        let used = min - storage.available.0;
+        assert!(used > 0);
+        testing_env!(context.attached_deposit(1000).build());
+        let storage = contract.storage_deposit(None, None);
+        assert_eq!(U128(min + 1000), storage.total);
+        testing_env!(context.attached_deposit(1).build());
+        let storage = contract.storage_withdraw(Some(U128(1000)));
+        assert_eq!(U128(min), storage.total);
+        let storage = contract.storage_withdraw(None);
+        assert_eq!(U128(used), storage.total);
+        assert_eq!(U128(0), storage.available);
     }
 
-    pub fn vote_reject(&mut self, proposal_id: u64) {
-        self.vote(proposal_id, false)
+    #[test]
+    fn vote_storage() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        deposit_storage(&mut contract, accounts(3));
+        contract.add_member(accounts(2).into());
+        testing_env!(context
+            .signer_account_id(accounts(3))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        let proposal_id = contract.add_member_proposal(None, None, None, None);
+        let proposal_id = match proposal_id {
+            PromiseOrValue::Value(proposal_id) => proposal_id,
+            _ => unreachable!(),
+        };
+        let author_storage = contract.storage_balance_of(accounts(3)).unwrap();
+        assert!(author_storage.available.0 < author_storage.total.0);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(proposal_id);
+        let voter_storage = contract.storage_balance_of(accounts(1)).unwrap();
+        assert!(voter_storage.available.0 < voter_storage.total.0);
     }
 
-    fn vote(&mut self, proposal_id: u64, resolve: bool) {
-        let signer_account_id = env::signer_account_id();
-        assert!(
-            self.is_member(signer_account_id.clone()),
-            "Only for members"
+    #[test]
+    fn add_member_proposal_without_nft() {
+        let mut context = new_context(accounts(1));
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(b"[]".to_vec())]
         );
-        let mut proposal = match self.proposal_list.get(proposal_id) {
-            Some(proposal) => proposal,
-            None => env::panic(b"Proposal not found"),
+        let mut contract = new_contract();
+        contract.policy.member_nft_contract_id = Some(accounts(3).into());
+        let proposal_id = contract.on_member_nft(MemberApplication {
+            account_id: accounts(2).into(),
+            title: None,
+            description: None,
+            bond: U128(PARAM_PROPOSAL_BOND),
+            proposal_id: None,
+            attachments: None,
+        });
+        assert_eq!(None, proposal_id);
+        assert_eq!(0, contract.fund_proposal);
+    }
+
+    #[test]
+    fn add_member_proposal_nft_limit() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.policy.member_nft_contract_id = Some(accounts(3).into());
+        testing_env!(
+            context.predecessor_account_id(accounts(0)).build(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            vec![PromiseResult::Successful(
+                json!([{"token_id": "42", "owner_id": accounts(2)}])
+                    .to_string()
+                    .into_bytes()
+            )]
+        );
+        let application = || MemberApplication {
+            account_id: accounts(2).into(),
+            title: None,
+            description: None,
+            bond: U128(PARAM_PROPOSAL_BOND),
+            proposal_id: None,
+            attachments: None,
         };
-        let mut vote_list = match self.vote_list.get(&proposal_id) {
-            Some(vote_list) => vote_list,
-            None => UnorderedSet::new(StorageKey::ProposalVote {
-                hash: hash(format!("{}{}", proposal_id, proposal.author)),
+        contract.policy.proposal_limit = 1;
+        assert_eq!(Some(0), contract.on_member_nft(application()));
+        assert_eq!(None, contract.on_member_nft(application()));
+        assert_eq!(PARAM_PROPOSAL_BOND, contract.fund_proposal);
+        assert!(get_logs().contains(&event_log(
+            "payout",
+            json!({
+                "receiver_id": accounts(2),
+                "amount": U128(PARAM_PROPOSAL_BOND),
+                "source": "proposal_bond",
             }),
-        };
-        if vote_list.contains(&signer_account_id) {
-            env::panic(b"You are already voted")
+        )));
+    }
+
+    #[test]
+    fn remove_member() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = Society::init(vec![accounts(1), accounts(2)]);
+        deposit_storage(&mut contract, accounts(1));
+        deposit_storage(&mut contract, accounts(2));
+        let proposal_id = contract.add_member_remove_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"account_id": accounts(2)}).to_string(),
+            None,
+            None,
+        );
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(proposal_id);
+        contract.update_profile(Some("Bob".to_string()), None, None, None);
+        let storage = contract.storage_balance_of(accounts(2)).unwrap().available;
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_approve(proposal_id);
+        assert!(
+            contract
+                .storage_balance_of(accounts(2))
+                .unwrap()
+                .available
+                .0
+                > storage.0
+        );
+        assert!(get_logs().contains(&event_log(
+            "member_removed",
+            json!({"account_id": accounts(2)}),
+        )));
+        assert!(!contract.is_member(accounts(2).into()));
+        assert!(contract.nft_token("member:charlie".to_string()).is_none());
+        assert_eq!(U128(0), contract.nft_supply_for_owner(accounts(2)));
+    }
+
+    #[test]
+    fn init_registers_members() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = Society::init(vec![accounts(1), accounts(2)]);
+        let storage = contract.storage_balance_of(accounts(2)).unwrap();
+        assert_eq!(U128(0), storage.total);
+        assert_eq!(U128(0), storage.available);
+    }
+
+    #[test]
+    fn is_not_member() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = new_contract();
+        assert!(!contract.is_member(accounts(2).into()));
+    }
+
+    #[test]
+    fn proposal_list_empty() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let contract = new_contract();
+        assert_eq!(0, contract.proposal_list(None, None).len());
+    }
+
+    #[test]
+    fn proposals() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        for (index, timestamp) in [(2, 10), (3, 20), (4, 30)].iter() {
+            deposit_storage(&mut contract, accounts(*index));
+            testing_env!(context
+                .signer_account_id(accounts(*index))
+                .block_timestamp(*timestamp)
+                .attached_deposit(PARAM_PROPOSAL_BOND)
+                .build());
+            contract.add_member_proposal(None, None, None, None);
         }
-        proposal.vote(resolve, self.vote_total());
-        if proposal.is_draft() {
-            vote_list = UnorderedSet::new(StorageKey::ProposalVote {
-                hash: hash(format!("{}{}", proposal_id, proposal.author)),
-            });
-            if proposal.kind == ProposalKind::FundRequest {
-                let script = proposal.script.clone();
-                let fund_script: FundScript =
-                    serde_json::from_str(&script.unwrap_or_else(|| "{}".to_string())).unwrap();
-                let request_fund = u128::from(fund_script.fund);
-                self.fund_proposal -= request_fund;
-            };
-        } else {
-            vote_list.insert(&signer_account_id);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(1);
+        let page = contract.proposals(None, None, Some(2), Some(ProposalOrder::Desc), Some(true));
+        assert_eq!(Some(3), page.total);
+        assert_eq!(
+            vec![2, 1],
+            page.proposals.iter().map(|p| p.id).collect::<Vec<_>>()
+        );
+        let page = contract.proposals(None, Some(2), Some(2), None, None);
+        assert_eq!(None, page.total);
+        assert_eq!(
+            vec![2],
+            page.proposals.iter().map(|p| p.id).collect::<Vec<_>>()
+        );
+        let filter: ProposalFilter = serde_json::from_value(json!({
+            "status": "Vote",
+            "kind": "MemberRequest",
+            "created_from": "15",
+        }))
+        .unwrap();
+        let page = contract.proposals(Some(filter), None, None, None, Some(true));
+        assert_eq!(Some(1), page.total);
+        assert_eq!(2, page.proposals[0].id);
+        let filter: ProposalFilter =
+            serde_json::from_value(json!({ "author": accounts(2), "created_to": "10" })).unwrap();
+        let page = contract.proposals(Some(filter), None, None, None, Some(true));
+        assert_eq!(Some(1), page.total);
+        assert_eq!(0, page.proposals[0].id);
+        let page = contract.proposals(None, None, Some(1), None, None);
+        assert_eq!(
+            vec![0],
+            page.proposals.iter().map(|p| p.id).collect::<Vec<_>>()
+        );
+        assert_eq!(2, contract.proposal_list(Some(1), Some(5)).len());
+    }
+
+    #[test]
+    fn proposals_by_author() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        deposit_storage(&mut contract, accounts(3));
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(Some("a".to_string()), None, None, None);
+        assert_eq!(vec![0], contract.active_proposal_of(accounts(2)));
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_reject(0);
+        assert!(contract.active_proposal_of(accounts(2)).is_empty());
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(Some("b".to_string()), None, None, None);
+        assert_eq!(vec![1], contract.active_proposal_of(accounts(2)));
+        assert_eq!("b", contract.get_proposal(1).unwrap().title);
+        assert!(contract.get_proposal(2).is_none());
+        let proposals = contract.proposals_by_author(accounts(2), None, None);
+        assert_eq!(
+            vec![0, 1],
+            proposals.iter().map(|p| p.id).collect::<Vec<_>>()
+        );
+        let proposals = contract.proposals_by_author(accounts(2), Some(1), Some(1));
+        assert_eq!(vec![1], proposals.iter().map(|p| p.id).collect::<Vec<_>>());
+        assert!(contract
+            .proposals_by_author(accounts(3), None, None)
+            .is_empty());
+    }
+
+    #[test]
+    fn concurrent_proposals() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        for proposal_id in 0..PARAM_PROPOSAL_LIMIT {
+            let id = contract.add_fund_proposal(
+                "a".to_string(),
+                "b".to_string(),
+                script.clone(),
+                None,
+                None,
+            );
+            assert_eq!(proposal_id, id);
         }
-        self.vote_list.insert(&proposal_id, &vote_list);
-        if proposal.is_accepted() {
-            self.active_proposal.remove(&proposal.author);
-            if proposal.kind == ProposalKind::MemberRequest {
-                self.add_member(proposal.author.clone());
-            };
-            if proposal.kind == ProposalKind::FundRequest {
-                let script = proposal.script.clone();
-                let fund_script: FundScript =
-                    serde_json::from_str(&script.unwrap_or_else(|| "{}".to_string())).unwrap();
-                let request_fund = u128::from(fund_script.fund);
-                self.fund_proposal -= request_fund;
-                Promise::new(proposal.author.clone()).transfer(request_fund);
-            };
-        };
-        self.proposal_list.replace(proposal_id, &proposal);
+        let proposal_id = contract.add_function_call_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"receiver_id": accounts(3), "actions": [{"method_name": "a", "args": "e30=", "deposit": "0", "gas": "1"}]})
+                .to_string(),
+            None,
+            None,
+        );
+        assert_eq!(PARAM_PROPOSAL_LIMIT, proposal_id);
+        assert_eq!(
+            (PARAM_PROPOSAL_LIMIT + 1) as usize,
+            contract.active_proposal_of(accounts(1)).len()
+        );
     }
 
-    pub fn add_member_proposal(
-        &mut self,
-        title: Option<String>,
-        description: Option<String>,
-    ) -> u64 {
-        let signer_account_id = env::signer_account_id();
-        self.assert_is_member(signer_account_id.clone());
-        self.add_proposal(
-            signer_account_id,
-            ProposalKind::MemberRequest,
-            ProposalStatus::Vote,
-            title,
-            description,
+    #[test]
+    #[should_panic(expected = "ERR_PROPOSAL_LIMIT")]
+    fn concurrent_proposals_over_limit() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        for _ in 0..=PARAM_PROPOSAL_LIMIT {
+            contract.add_fund_proposal(
+                "a".to_string(),
+                "b".to_string(),
+                script.clone(),
+                None,
+                None,
+            );
+        }
+    }
+
+    #[test]
+    fn update_draft_proposal() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        deposit_storage(&mut contract, accounts(3));
+        contract.add_member(accounts(2).into());
+        testing_env!(context
+            .signer_account_id(accounts(3))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(Some("a".to_string()), None, None, None);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(0);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_reject(0);
+        assert!(contract.get_proposal(0).unwrap().status == ProposalStatus::Draft);
+        testing_env!(context
+            .signer_account_id(accounts(3))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .block_timestamp(PARAM_TIME_LOCK + 1)
+            .build());
+        contract.add_member_proposal(Some("b".to_string()), None, Some(0), None);
+        let proposal = contract.get_proposal(0).unwrap();
+        assert!(proposal.status == ProposalStatus::Vote);
+        assert_eq!("b", proposal.title);
+        assert_eq!(vec![0], contract.active_proposal_of(accounts(3)));
+    }
+
+    #[test]
+    fn update_proposal() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script.clone(), None, None);
+        testing_env!(context.attached_deposit(0).build());
+        contract.vote_approve(0);
+        contract.update_proposal(0, Some("c".to_string()), None, Some(script), None);
+        let proposal = contract.get_proposal(0).unwrap();
+        assert_eq!("c", proposal.title);
+        assert_eq!("b", proposal.description);
+        assert_eq!(1, proposal.vote.approve);
+        assert!(contract
+            .vote_list
+            .get(&0)
+            .unwrap()
+            .contains(&accounts(1).into()));
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_reject(0);
+        assert!(contract.get_proposal(0).unwrap().status == ProposalStatus::Draft);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .block_timestamp(PARAM_TIME_LOCK + 1)
+            .build());
+        contract.update_proposal(
+            0,
             None,
-        )
+            Some("d".to_string()),
+            Some("{\"fund\": \"2000000000000000000000000\"}".to_string()),
+            None,
+        );
+        let proposal = contract.get_proposal(0).unwrap();
+        assert_eq!("c", proposal.title);
+        assert_eq!("d", proposal.description);
+        assert_eq!(0, proposal.vote.approve);
+        assert!(contract.vote_list.get(&0).is_none());
+        assert_eq!(
+            2000000000000000000000000 + PARAM_PROPOSAL_BOND,
+            contract.fund_proposal
+        );
+        let revisions = contract.proposal_revisions(0);
+        assert_eq!(
+            vec!["a", "c"],
+            revisions
+                .iter()
+                .map(|revision| revision.title.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some("{\"fund\": \"1000000000000000000000000\"}".to_string()),
+            revisions[1].script
+        );
     }
 
-    pub fn add_fund_proposal(&mut self, title: String, description: String, script: String) -> u64 {
-        let signer_account_id = env::signer_account_id();
+    #[test]
+    fn update_proposal_description() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(0);
+        let storage = contract.storage_balance_of(accounts(2)).unwrap().available;
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_reject(0);
         assert!(
-            self.is_member(signer_account_id.clone()),
-            "Only for members"
+            contract
+                .storage_balance_of(accounts(2))
+                .unwrap()
+                .available
+                .0
+                > storage.0
         );
-        let fund_script: FundScript = serde_json::from_str(&script).unwrap();
-        let request_fund = u128::from(fund_script.fund);
-        // TODO move into method
-        let curren_fund = env::account_balance()
-            - env::account_locked_balance()
-            - env::storage_byte_cost() * Balance::from(env::storage_usage())
-            - PARAM_FUND_RESERVE
-            - self.fund_proposal;
-        if request_fund >= curren_fund {
-            env::panic(b"The fund does not have so many resources")
-        };
-        self.fund_proposal += request_fund;
-        self.add_proposal(
-            signer_account_id,
-            ProposalKind::FundRequest,
-            ProposalStatus::Vote,
-            Some(title),
-            Some(description),
-            Some(script),
-        )
+        testing_env!(context.block_timestamp(100).attached_deposit(1).build());
+        contract.update_proposal(0, None, Some("c".to_string()), None, None);
+        let proposal = contract.get_proposal(0).unwrap();
+        assert_eq!("c", proposal.description);
+        assert_eq!(0, proposal.vote.approve);
+        assert!(contract.vote_list.get(&0).is_none());
+        assert_eq!(U64(100), contract.proposal_revisions(0)[0].timestamp);
     }
 
-    fn assert_is_member(&mut self, account_id: AccountId) {
-        if self.member_list.contains(&account_id) {
-            env::panic(format!("Account {} already is member", account_id).as_bytes())
-        }
+    #[test]
+    #[should_panic(
+        expected = "ERR_PROPOSAL_NOT_DRAFT: You can update the proposal 0 only in the draft status"
+    )]
+    fn update_proposal_in_vote() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        contract.add_member(accounts(2).into());
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        testing_env!(context
+            .attached_deposit(0)
+            .block_timestamp(PARAM_TIME_LOCK + 1)
+            .build());
+        contract.update_proposal(0, None, Some("c".to_string()), None, None);
     }
 
-    fn add_proposal(
-        &mut self,
-        author: AccountId,
-        kind: ProposalKind,
-        status: ProposalStatus,
-        title: Option<String>,
-        description: Option<String>,
-        script: Option<String>,
-    ) -> u64 {
-        match self.active_proposal.get(&author) {
-            Some(proposal_id) => {
-                let proposal = match self.proposal_list.get(proposal_id) {
-                    Some(proposal) => proposal,
-                    None => env::panic(format!("Proposal {} not active", proposal_id).as_bytes()),
-                };
-                if proposal.timestamp + PARAM_TIME_LOCK >= env::block_timestamp() {
-                    env::panic(format!("Proposal {} is locked try late", proposal_id).as_bytes())
-                }
-                if proposal.status != ProposalStatus::Draft {
-                    env::panic(
-                        format!(
-                            "You can update the proposal {} only in the draft status",
-                            proposal_id,
-                        )
-                        .as_bytes(),
-                    )
-                }
-                self.proposal_list.replace(
-                    proposal_id,
-                    &ProposalState::new(title, description, author, kind, status, script),
-                );
-                proposal_id
-            }
-            None => {
-                let proposal_id = self.proposal_list.len();
-                self.active_proposal.insert(&author, &proposal_id);
-                self.proposal_list.push(&ProposalState::new(
-                    title,
-                    description,
-                    author,
-                    kind,
-                    status,
-                    script,
-                ));
-                proposal_id
-            }
-        }
+    #[test]
+    #[should_panic(expected = "ERR_COMMENT_LIMIT: Proposal cannot have more than 1 comments")]
+    fn proposal_comments_policy_limit() {
+        let context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        let proposal_id = contract.add_policy_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"comment_limit": 1}).to_string(),
+            None,
+            None,
+        );
+        contract.vote_approve(proposal_id);
+        assert_eq!(1, contract.policy.comment_limit);
+        assert_eq!(PARAM_COMMENT_LEN_MAX, contract.policy.comment_len_max);
+        let proposal_id = contract.add_policy_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({}).to_string(),
+            None,
+            None,
+        );
+        contract.add_comment(proposal_id, "a".to_string(), None);
+        contract.add_comment(proposal_id, "b".to_string(), None);
     }
 
-    pub fn member_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<AccountId> {
-        let limit = limit.unwrap_or(100);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
-        let start_index: u64 = offset.unwrap_or(0);
-        assert!(
-            self.member_list.len() > start_index,
-            "Out of bounds, please use a smaller from_index."
+    #[test]
+    fn proposal_comments() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        assert_eq!(0, contract.add_comment(0, "spam".to_string(), None));
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        assert_eq!(1, contract.add_comment(0, "why?".to_string(), Some(0)));
+        let proposal_id = contract.add_comment_hide_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            json!({"proposal_id": "0", "comment_id": "0"}).to_string(),
+            None,
+            None,
         );
-        self.member_list
-            .iter()
-            .skip(start_index as usize)
-            .take(limit as usize)
-            .collect()
+        contract.vote_approve(proposal_id);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_approve(proposal_id);
+        let comments = contract.proposal_comments(0, None, None);
+        assert_eq!(2, comments.len());
+        assert!(comments[0].hidden);
+        assert_eq!(None, comments[0].text);
+        assert_eq!(Some("why?".to_string()), comments[1].text);
+        assert_eq!(Some(0), comments[1].reply_to);
+        assert_eq!(1, contract.proposal_comments(0, Some(1), Some(1)).len());
     }
 
-    pub fn proposal_list(self, offset: Option<u64>, limit: Option<u64>) -> Vec<Proposal> {
-        let limit = limit.unwrap_or(100);
-        assert_ne!(limit, 0, "Cannot provide limit of 0.");
-        let start_index: u64 = offset.unwrap_or(0);
-        assert!(
-            self.member_list.len() > start_index,
-            "Out of bounds, please use a smaller from_index."
-        );
-        let mut id = start_index;
-        let mut result = vec![];
-        for state in self.proposal_list.iter().skip(start_index as usize) {
-            result.push(Proposal {
-                id,
-                timestamp: state.timestamp,
-                title: state.title,
-                kind: state.kind,
-                status: state.status,
-                description: state.description,
-                script: state.script,
-                author: state.author,
-                vote: state.vote,
-            });
-            id += 1
-        }
-        result
+    #[test]
+    #[should_panic(expected = "ERR_ONLY_MEMBER")]
+    fn add_comment_not_member() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        testing_env!(context.signer_account_id(accounts(3)).build());
+        contract.add_comment(0, "hi".to_string(), None);
     }
-}
 
-#[cfg(test)]
-mod unit {
-    use super::*;
-    use near_sdk::json_types::ValidAccountId;
-    use near_sdk::test_utils::accounts;
-    use near_sdk::test_utils::VMContextBuilder;
-    use near_sdk::testing_env;
-    use near_sdk::MockedBlockchain;
-    use std::convert::TryFrom;
+    #[test]
+    fn proposal_attachments() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let attachments: Vec<Attachment> = serde_json::from_value(json!([{
+            "uri": "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "hash": near_sdk::base64::encode(env::sha256(b"budget")),
+            "mime_type": "application/pdf",
+            "label": "Budget",
+        }]))
+        .unwrap();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script,
+            None,
+            Some(attachments),
+        );
+        let proposal = contract.get_proposal(0).unwrap();
+        assert_eq!(1, proposal.attachments.len());
+        assert_eq!("application/pdf", proposal.attachments[0].mime_type);
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(0);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_reject(0);
+        assert!(contract.get_proposal(0).unwrap().status == ProposalStatus::Draft);
+        let attachments: Vec<Attachment> = serde_json::from_value(json!([{
+            "uri": "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "hash": near_sdk::base64::encode(env::sha256(b"budget2")),
+            "mime_type": "application/pdf",
+            "label": "Budget",
+        }]))
+        .unwrap();
+        contract.update_proposal(0, None, None, None, Some(attachments));
+        let proposal = contract.get_proposal(0).unwrap();
+        assert_eq!(0, proposal.vote.approve);
+        assert!(contract.vote_list.get(&0).is_none());
+        assert_eq!(
+            "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            proposal.attachments[0].uri
+        );
+        assert_eq!(
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            contract.proposal_revisions(0)[0].attachments[0].uri
+        );
+    }
 
-    pub fn new_context(predecessor_account_id: ValidAccountId) -> VMContextBuilder {
-        let mut builder = VMContextBuilder::new();
-        builder
-            .current_account_id(accounts(0))
-            .signer_account_id(predecessor_account_id.clone())
-            .predecessor_account_id(predecessor_account_id);
-        builder
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_FIELD: Field attachment uri")]
+    fn proposal_attachments_short_cid() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let attachments: Vec<Attachment> = serde_json::from_value(json!([{
+            "uri": "bafy",
+            "hash": near_sdk::base64::encode(env::sha256(b"a")),
+            "mime_type": "image/png",
+            "label": "Portfolio",
+        }]))
+        .unwrap();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal(
+            "a".to_string(),
+            "b".to_string(),
+            script,
+            None,
+            Some(attachments),
+        );
     }
 
-    pub fn new_contract() -> Society {
-        Society::init(vec![accounts(1)])
+    #[test]
+    #[should_panic(expected = "ERR_INVALID_FIELD: Field attachment hash")]
+    fn proposal_attachments_without_hash() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        let attachments: Vec<Attachment> = serde_json::from_value(json!([{
+            "uri": "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG",
+            "hash": "",
+            "mime_type": "image/png",
+            "label": "Portfolio",
+        }]))
+        .unwrap();
+        contract.update_proposal(0, None, None, None, Some(attachments));
     }
 
     #[test]
-    fn consensus_cases() {
-        assert!(consensus(1, 1));
-        assert_eq!(!consensus(2, 1), consensus(2, 2));
-        assert_eq!(!consensus(3, 1), consensus(3, 2));
-        assert_eq!(!consensus(4, 2), consensus(4, 3));
-        assert_eq!(!consensus(5, 2), consensus(5, 3));
-        assert_eq!(!consensus(6, 3), consensus(6, 4));
-        assert_eq!(!consensus(7, 3), consensus(7, 4));
-        assert_eq!(!consensus(8, 4), consensus(8, 5));
-        assert_eq!(!consensus(9, 4), consensus(9, 5));
-        assert_eq!(!consensus(10, 5), consensus(10, 6));
-        assert_eq!(!consensus(11, 5), consensus(11, 6));
-        assert_eq!(!consensus(12, 6), consensus(12, 7));
-        assert_eq!(!consensus(13, 6), consensus(13, 7));
+    #[should_panic(expected = "ERR_INVALID_FIELD: Field attachment uri")]
+    fn proposal_attachments_invalid_uri() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        let attachments: Vec<Attachment> = serde_json::from_value(json!([{
+            "uri": "ftp://example.com/a.png",
+            "hash": near_sdk::base64::encode(env::sha256(b"a")),
+            "mime_type": "image/png",
+            "label": "Portfolio",
+        }]))
+        .unwrap();
+        contract.update_proposal(0, None, None, None, Some(attachments));
     }
 
     #[test]
-    fn balance() {
-        let context = new_context(accounts(1));
-        testing_env!(context.build());
-        let contract = new_contract();
-        assert_eq!(U128(86926860000000000000000000), contract.balance());
+    #[should_panic(expected = "ERR_PROPOSAL_NOT_ACTIVE")]
+    fn update_proposal_of_other() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        let script = "{\"fund\": \"1000000000000000000000000\"}".to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.update_proposal(0, Some("c".to_string()), None, None, None);
     }
 
     #[test]
-    fn member_list() {
-        let context = new_context(accounts(1));
-        testing_env!(context.build());
-        let contract = new_contract();
-        assert_eq!(
-            vec![accounts(1).into()] as Vec<AccountId>,
-            contract.member_list(None, None)
-        );
+    #[should_panic(expected = "ERR_ALREADY_MEMBER")]
+    fn add_member_proposal_for_exist() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        contract.add_member_proposal(None, None, None, None);
     }
 
     #[test]
-    fn is_member() {
-        let context = new_context(accounts(1));
+    fn add_member_proposal() {
+        let mut context = new_context(accounts(1));
         testing_env!(context.build());
-        let contract = new_contract();
-        assert!(contract.is_member(accounts(1).into()));
+        let mut contract = new_contract();
+        let account_id = ValidAccountId::try_from("a".repeat(64)).unwrap();
+        testing_env!(context
+            .predecessor_account_id(account_id.clone())
+            .attached_deposit(storage_cost(PARAM_STORAGE_ACCOUNT + PARAM_STORAGE_PROPOSAL))
+            .build());
+        contract.storage_deposit(None, None);
+        testing_env!(context
+            .signer_account_id(account_id)
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        assert!(matches!(
+            contract.add_member_proposal(Some("a".repeat(170)), Some("a".repeat(1000)), None, None),
+            PromiseOrValue::Value(0)
+        ));
+        assert_eq!(1, contract.proposal_list(None, None).len());
     }
 
     #[test]
-    fn add_fund_proposal() {
-        let context = new_context(accounts(1));
+    #[should_panic(expected = "ERR_BOND_REQUIRED")]
+    fn add_member_proposal_without_bond() {
+        let mut context = new_context(accounts(1));
         testing_env!(context.build());
         let mut contract = new_contract();
-        let proposal_id = contract.add_fund_proposal(
-            "a".to_string(),
-            "b".to_string(),
-            "{\"fund\": \"1000000000000000000000000\"}".to_string(),
-        );
-        assert_eq!(0, proposal_id);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.add_member_proposal(None, None, None, None);
     }
 
     #[test]
-    fn is_not_member() {
-        let context = new_context(accounts(1));
+    fn vote_spam() {
+        let mut context = new_context(accounts(1));
         testing_env!(context.build());
-        let contract = new_contract();
-        assert!(!contract.is_member(accounts(2).into()));
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(None, None, None, None);
+        assert_eq!(PARAM_PROPOSAL_BOND, contract.fund_proposal);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_spam(0);
+        assert!(get_logs().contains(&event_log(
+            "vote_cast",
+            json!({"proposal_id": 0, "account_id": accounts(1), "vote": "spam"}),
+        )));
+        assert!(get_logs().contains(&event_log(
+            "proposal_status_changed",
+            json!({"proposal_id": 0, "status": "Rejected"}),
+        )));
+        assert_eq!(0, contract.fund_proposal);
+        assert!(contract.active_proposal_of(accounts(2)).is_empty());
+        let proposal = &contract.proposal_list(None, None)[0];
+        assert!(proposal.status == ProposalStatus::Rejected);
+        assert_eq!(1, proposal.vote.spam);
     }
 
     #[test]
-    fn proposal_list_empty() {
-        let context = new_context(accounts(1));
-        testing_env!(context.build());
-        let contract = new_contract();
-        assert_eq!(0, contract.proposal_list(None, None).len());
+    #[should_panic(expected = "ERR_PROPOSAL_NOT_IN_VOTE")]
+    fn vote_tie_draft() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.attached_deposit(PARAM_PROPOSAL_BOND).build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        contract.add_member(accounts(2).into());
+        let script = json!({"fund": "5000"}).to_string();
+        contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        let script = json!({"fund": "1000"}).to_string();
+        let proposal_id =
+            contract.add_fund_proposal("a".to_string(), "b".to_string(), script, None, None);
+        assert_eq!(2 * PARAM_PROPOSAL_BOND + 6000, contract.fund_proposal);
+        let available = contract.storage_balance_of(accounts(1)).unwrap().available;
+        testing_env!(context.attached_deposit(0).build());
+        contract.vote_approve(proposal_id);
+        testing_env!(context.signer_account_id(accounts(1)).build());
+        contract.vote_reject(proposal_id);
+        assert!(contract.get_proposal(proposal_id).unwrap().status == ProposalStatus::Draft);
+        assert_eq!(2 * PARAM_PROPOSAL_BOND + 5000, contract.fund_proposal);
+        assert_eq!(
+            available,
+            contract.storage_balance_of(accounts(1)).unwrap().available
+        );
+        contract.vote_approve(proposal_id);
     }
 
     #[test]
-    #[should_panic(expected = "Account bob already is member")]
-    fn add_member_proposal_for_exist() {
-        let context = new_context(accounts(1));
+    #[should_panic(expected = "ERR_PROPOSAL_FINALIZED")]
+    fn vote_finalized() {
+        let mut context = new_context(accounts(1));
         testing_env!(context.build());
         let mut contract = new_contract();
-        contract.add_member_proposal(None, None);
+        deposit_storage(&mut contract, accounts(2));
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(None, None, None, None);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.vote_approve(0);
+        testing_env!(context.signer_account_id(accounts(2)).build());
+        contract.vote_reject(0);
     }
 
     #[test]
-    fn add_member_proposal() {
+    fn archive_proposal() {
         let mut context = new_context(accounts(1));
         testing_env!(context.build());
         let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
         testing_env!(context
-            .signer_account_id(ValidAccountId::try_from("a".repeat(64)).unwrap())
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
             .build());
-        assert_eq!(
-            0,
-            contract.add_member_proposal(Some("a".repeat(170)), Some("a".repeat(1000)))
+        contract.add_member_proposal(Some("a".repeat(170)), Some("a".repeat(1000)), None, None);
+        testing_env!(context
+            .signer_account_id(accounts(1))
+            .attached_deposit(0)
+            .build());
+        contract.add_comment(0, "a".repeat(500), None);
+        contract.vote_approve(0);
+        let liquid = contract.balance().liquid.0;
+        let storage_usage = env::storage_usage();
+        let voter = contract
+            .storage_balance_of(accounts(1))
+            .unwrap()
+            .available
+            .0;
+        let author = contract
+            .storage_balance_of(accounts(2))
+            .unwrap()
+            .available
+            .0;
+        contract.archive_proposal(0);
+        assert!(env::storage_usage() < storage_usage);
+        assert_eq!(liquid, contract.balance().liquid.0);
+        assert!(
+            contract
+                .storage_balance_of(accounts(1))
+                .unwrap()
+                .available
+                .0
+                > voter
         );
-        assert_eq!(1, contract.proposal_list(None, None).len());
+        assert!(
+            contract
+                .storage_balance_of(accounts(2))
+                .unwrap()
+                .available
+                .0
+                > author
+        );
+        assert!(contract.vote_list.get(&0).is_none());
+        let proposal = &contract.proposal_list(None, None)[0];
+        assert!(proposal.archived);
+        assert!(proposal.status == ProposalStatus::Accepted);
+        assert_eq!("a".repeat(170), proposal.title);
+        assert_eq!("", proposal.description);
+    }
+
+    #[test]
+    #[should_panic(expected = "ERR_PROPOSAL_NOT_FINALIZED")]
+    fn archive_proposal_in_vote() {
+        let mut context = new_context(accounts(1));
+        testing_env!(context.build());
+        let mut contract = new_contract();
+        deposit_storage(&mut contract, accounts(2));
+        testing_env!(context
+            .signer_account_id(accounts(2))
+            .attached_deposit(PARAM_PROPOSAL_BOND)
+            .build());
+        contract.add_member_proposal(None, None, None, None);
+        contract.archive_proposal(0);
     }
 }
 #[cfg(test)]
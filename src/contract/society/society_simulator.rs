@@ -10,7 +10,20 @@ fn vote() {
 
     // 1 exist members for 2nd member need 1 approve
     let proposal_id = 0;
-    call_vote_approve(&contract, &contract, proposal_id);
+    let result = call(
+        &contract,
+        &contract,
+        "vote_approve",
+        json!({ "proposal_id": proposal_id }),
+        0, // deposit
+    );
+    let event = json!({
+        "standard": "cryptoartdao",
+        "version": "1.0.0",
+        "event": "member_added",
+        "data": [{"account_id": list[0].account_id()}],
+    });
+    assert!(result.logs().contains(&format!("EVENT_JSON:{}", event)));
     assert_eq!(2, member_total(&contract));
 
     // 2 exist members for 3rd member need 2 approve
@@ -141,7 +154,7 @@ fn create_member_proposal_for_all() -> (UserAccount, Vec<UserAccount>) {
                 "title": "a".repeat(170),
                 "description": "a".repeat(1000),
             }),
-            0, // deposit
+            to_yocto("0.1"), // bond
         );
         assert_eq!(
             0,
@@ -168,9 +181,16 @@ fn add_member_proposal() {
             "title": "a".repeat(170),
             "description": "a".repeat(1000),
         }),
-        0, // deposit
+        to_yocto("0.1"), // bond
     );
     assert_burnt_gas("add_member_proposal_1", &result, "4.5", None);
+    let event = json!({
+        "standard": "cryptoartdao",
+        "version": "1.0.0",
+        "event": "proposal_created",
+        "data": [{"proposal_id": 0, "author": list[0].account_id(), "kind": "MemberRequest"}],
+    });
+    assert!(result.logs().contains(&format!("EVENT_JSON:{}", event)));
     let actual: Vec<Proposal> = contract
         .view(contract.account_id(), "proposal_list", &args(json!({})))
         .unwrap_json();
@@ -236,6 +256,21 @@ pub fn deploy() -> (UserAccount, Vec<UserAccount>) {
         "got error: {:#?}",
         result.promise_errors()
     );
+    for user in list.iter().chain(std::iter::once(&contract)) {
+        let result = call(
+            &contract,
+            user,
+            "storage_deposit",
+            json!({}),
+            to_yocto("0.1"), // storage
+        );
+        assert_eq!(
+            0,
+            result.promise_errors().len(),
+            "got error: {:#?}",
+            result.promise_errors()
+        );
+    }
     (contract, list)
 }
 